@@ -0,0 +1,219 @@
+//! Fairy piece movements in Betza notation.
+//!
+//! A piece is described by atoms, each a leap: `W` (1, 0), `F` (1, 1), `D` (2, 0),
+//! `N` (2, 1), `A` (2, 2), `H` (3, 0), `C` (3, 1), `Z` (3, 2) and `G` (3, 3), plus the
+//! shorthands `K` (`WF`), `R` (`WW`), `B` (`FF`) and `Q` (`RB`). A doubled atom is a rider
+//! (`NN` is the nightrider), a number gives the maximum range (`R4`). Modifiers before an
+//! atom restrict it: `m` to moves and `c` to captures, `f`, `b`, `l`, `r`, `v` and `s`
+//! (forward, backward, left, right, vertical, sideways) to directions, seen from the
+//! piece's side: Black's pieces move rotated by half a turn.
+//! Two direction letters combine, so `fr` is forward-right and `ff` forward-narrow.
+
+use ::error::*;
+
+/// Whether a leap may move to empty squares, capture, or both.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LeapMode {
+    Both,
+    Move,
+    Capture,
+}
+
+/// One direction of a piece's movement: a leap by `files` and `ranks` (seen from White),
+/// repeated up to `range` times as long as the squares in between are empty.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Leap {
+    files: i32,
+    ranks: i32,
+    range: u32,
+    mode: LeapMode,
+}
+
+impl Leap {
+    pub fn get_files(&self) -> i32 {
+        self.files
+    }
+
+    pub fn get_ranks(&self) -> i32 {
+        self.ranks
+    }
+
+    /// Returns the maximum number of steps, `u32::MAX` for an unlimited rider.
+    pub fn get_range(&self) -> u32 {
+        self.range
+    }
+
+    pub fn get_mode(&self) -> LeapMode {
+        self.mode
+    }
+}
+
+/// Movement rules of a piece, compiled from Betza notation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PieceRules {
+    leaps: Vec<Leap>,
+}
+
+impl PieceRules {
+    /// Compiles a piece described in Betza notation (see the module documentation).
+    pub fn from_betza(notation: &str) -> Result<PieceRules, BoardError> {
+        let error = |text: &'static str| BoardError::Betza(text, notation.to_string());
+        let chars: Vec<char> = notation.chars().collect();
+        // leaps as (files, ranks, range, (moves, captures))
+        let mut leaps: Vec<(i32, i32, u32, (bool, bool))> = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let mut mode = (false, false);
+            let mut dirs = String::new();
+            while i < chars.len() && "mcfblrvs".contains(chars[i]) {
+                match chars[i] {
+                    'm' => mode.0 = true,
+                    'c' => mode.1 = true,
+                    c => dirs.push(c),
+                }
+                i += 1;
+            }
+            let atom = *chars.get(i).ok_or_else(|| error("Betza modifiers without an atom"))?;
+            let steps = atom_steps(atom).ok_or_else(|| error("Unknown Betza atom"))?;
+            i += 1;
+            // rook, bishop and queen slide without a range
+            let mut range = if matches!(atom, 'R' | 'B' | 'Q') { u32::MAX } else { 1 };
+            if chars.get(i) == Some(&atom) {
+                range = u32::MAX;
+                i += 1;
+            } else if chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+                let start = i;
+                while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+                    i += 1;
+                }
+                let digits: String = chars[start..i].iter().collect();
+                range = digits.parse().map_err(|_| error("Wrong Betza range"))?;
+                if range == 0 {
+                    return Err(error("Wrong Betza range"));
+                }
+            }
+            if mode == (false, false) {
+                mode = (true, true);
+            }
+            let filters = direction_filters(&dirs);
+            for &(x, y) in steps {
+                for &(files, ranks) in &symmetries(x, y) {
+                    let allowed = |f: &Vec<char>| {
+                        f.iter().all(|&d| in_direction(d, files, ranks))
+                    };
+                    if !filters.is_empty() && !filters.iter().any(allowed) {
+                        continue;
+                    }
+                    // a longer leap on the same step makes the same moves and more (`WR` is
+                    // just `R`), so only what is not covered yet is kept
+                    let mut new = mode;
+                    for l in leaps.iter().filter(|l| (l.0, l.1) == (files, ranks) && l.2 >= range) {
+                        new = (new.0 && !(l.3).0, new.1 && !(l.3).1);
+                    }
+                    if new == (false, false) {
+                        continue;
+                    }
+                    for l in leaps.iter_mut().filter(|l| (l.0, l.1) == (files, ranks)) {
+                        if l.2 < range {
+                            l.3 = ((l.3).0 && !new.0, (l.3).1 && !new.1);
+                        } else if l.2 == range {
+                            l.3 = ((l.3).0 || new.0, (l.3).1 || new.1);
+                            new = (false, false);
+                        }
+                    }
+                    if new != (false, false) {
+                        leaps.push((files, ranks, range, new));
+                    }
+                }
+            }
+        }
+        let leaps: Vec<Leap> = leaps.into_iter()
+            .filter_map(|(files, ranks, range, mode)| {
+                let mode = match mode {
+                    (true, true) => LeapMode::Both,
+                    (true, false) => LeapMode::Move,
+                    (false, true) => LeapMode::Capture,
+                    (false, false) => return None,
+                };
+                Some(Leap { files, ranks, range, mode })
+            })
+            .collect();
+        if leaps.is_empty() {
+            return Err(error("Empty Betza notation"));
+        }
+        Ok(PieceRules { leaps })
+    }
+
+    pub fn get_leaps(&self) -> &[Leap] {
+        &self.leaps
+    }
+}
+
+/// Basic steps of an atom.
+fn atom_steps(atom: char) -> Option<&'static [(i32, i32)]> {
+    Some(match atom {
+        'W' | 'R' => &[(1, 0)],
+        'F' | 'B' => &[(1, 1)],
+        'K' | 'Q' => &[(1, 0), (1, 1)],
+        'D' => &[(2, 0)],
+        'N' => &[(2, 1)],
+        'A' => &[(2, 2)],
+        'H' => &[(3, 0)],
+        'C' => &[(3, 1)],
+        'Z' => &[(3, 2)],
+        'G' => &[(3, 3)],
+        _ => return None,
+    })
+}
+
+/// The distinct leaps obtained by rotating and mirroring a step.
+fn symmetries(x: i32, y: i32) -> Vec<(i32, i32)> {
+    let mut res = Vec::new();
+    for &(a, b) in &[(x, y), (y, x)] {
+        for &(sa, sb) in &[(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+            if !res.contains(&(a * sa, b * sb)) {
+                res.push((a * sa, b * sb));
+            }
+        }
+    }
+    res
+}
+
+/// Splits direction modifiers into groups of one or two letters that must all hold.
+fn direction_filters(dirs: &str) -> Vec<Vec<char>> {
+    let chars: Vec<char> = dirs.chars().collect();
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let first = chars[i];
+        let combines = |second: char| match first {
+            'f' | 'b' => second == first || "lrs".contains(second),
+            'l' | 'r' => second == first || second == 'v',
+            _ => false,
+        };
+        match chars.get(i + 1) {
+            Some(&second) if combines(second) => {
+                // a doubled letter is narrowed to the longer side of the leap
+                let narrow = if "fb".contains(first) { 'v' } else { 's' };
+                res.push(vec![first, if second == first { narrow } else { second }]);
+                i += 2;
+            }
+            _ => {
+                res.push(vec![first]);
+                i += 1;
+            }
+        }
+    }
+    res
+}
+
+fn in_direction(dir: char, files: i32, ranks: i32) -> bool {
+    match dir {
+        'f' => ranks > 0,
+        'b' => ranks < 0,
+        'r' => files > 0,
+        'l' => files < 0,
+        'v' => ranks.abs() > files.abs(),
+        _ => files.abs() > ranks.abs(),
+    }
+}
@@ -0,0 +1,332 @@
+extern crate rusty_board;
+
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use rusty_board::board::*;
+use rusty_board::moves::*;
+use rusty_board::search::*;
+use rusty_board::square::*;
+use rusty_board::timeman::*;
+use rusty_board::variant::*;
+
+/// Parameters of the `go` command.
+#[derive(Default)]
+struct GoParams {
+    depth: Option<u32>,
+    nodes: Option<u64>,
+    movetime: Option<u64>,
+    wtime: Option<u64>,
+    btime: Option<u64>,
+    winc: Option<u64>,
+    binc: Option<u64>,
+    movestogo: Option<u64>,
+    infinite: bool,
+    ponder: bool,
+}
+
+impl GoParams {
+    fn parse(tokens: &[&str]) -> GoParams {
+        let mut res = GoParams::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            let value = tokens.get(i + 1).and_then(|v| v.parse::<i64>().ok()).map(|v| v.max(0));
+            match tokens[i] {
+                "infinite" => res.infinite = true,
+                "ponder" => res.ponder = true,
+                "depth" => res.depth = value.map(|v| v as u32),
+                "nodes" => res.nodes = value.map(|v| v as u64),
+                "movetime" => res.movetime = value.map(|v| v as u64),
+                "wtime" => res.wtime = value.map(|v| v as u64),
+                "btime" => res.btime = value.map(|v| v as u64),
+                "winc" => res.winc = value.map(|v| v as u64),
+                "binc" => res.binc = value.map(|v| v as u64),
+                "movestogo" => res.movestogo = value.map(|v| v as u64),
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            }
+            i += if tokens[i] == "infinite" || tokens[i] == "ponder" { 1 } else { 2 };
+        }
+        res
+    }
+
+    /// Clock state of the side to move, if given.
+    fn time_control(&self, side: Color) -> Option<TimeControl> {
+        let (time, inc) = match side {
+            Color::White => (self.wtime, self.winc),
+            Color::Black => (self.btime, self.binc),
+        };
+        Some(TimeControl {
+            remaining: Duration::from_millis(time?),
+            increment: match inc {
+                Some(inc) if inc > 0 => Increment::Fischer(Duration::from_millis(inc)),
+                _ => Increment::None,
+            },
+            moves_to_go: self.movestogo.map(|m| m as u32),
+        })
+    }
+}
+
+struct Engine {
+    board: Board,
+    variant: &'static dyn Variant,
+    chess960: bool,
+    move_overhead: Duration,
+    search: Option<Search>,
+    stop: Arc<AtomicBool>,
+    ponder: Arc<AtomicBool>,
+    infinite: bool,
+    worker: Option<thread::JoinHandle<Search>>,
+}
+
+impl Engine {
+    fn new() -> Engine {
+        let search = Search::new();
+        Engine {
+            board: Board::new(),
+            variant: &STANDARD,
+            chess960: false,
+            move_overhead: Duration::from_millis(30),
+            stop: search.stop_handle(),
+            ponder: search.ponder_handle(),
+            search: Some(search),
+            infinite: false,
+            worker: None,
+        }
+    }
+
+    /// Castling is written as king takes rook.
+    fn is_chess960(&self) -> bool {
+        self.chess960 || self.variant.is_chess960()
+    }
+
+    /// Waits for the running search (if any) to finish.
+    fn wait(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            self.search = Some(worker.join().unwrap());
+        }
+    }
+
+    fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        self.wait();
+    }
+
+    fn set_option(&mut self, tokens: &[&str]) {
+        let name_end = tokens.iter().position(|&t| t == "value").unwrap_or(tokens.len());
+        let name = tokens[..name_end].iter().skip(1).cloned().collect::<Vec<&str>>().join(" ");
+        let value = tokens.get(name_end + 1..).map(|v| v.join(" ")).unwrap_or_default();
+        match name.to_lowercase().as_str() {
+            "uci_chess960" => self.chess960 = value == "true",
+            "uci_variant" => {
+                match variant_by_name(&value) {
+                    Some(v) => {
+                        self.variant = v;
+                        self.board = Board::new_variant(v);
+                    }
+                    None => println!("info string unknown variant {}", value),
+                }
+            }
+            "move overhead" => {
+                match value.parse() {
+                    Ok(ms) => self.move_overhead = Duration::from_millis(ms),
+                    Err(_) => println!("info string wrong value {}", value),
+                }
+            }
+            "threads" => {
+                match value.parse() {
+                    Ok(n) => self.search.as_mut().unwrap().set_threads(n),
+                    Err(_) => println!("info string wrong value {}", value),
+                }
+            }
+            "multipv" => {
+                match value.parse() {
+                    Ok(n) => self.search.as_mut().unwrap().set_multi_pv(n),
+                    Err(_) => println!("info string wrong value {}", value),
+                }
+            }
+            "hash" => {
+                match value.parse() {
+                    Ok(mb) => self.search.as_mut().unwrap().set_hash_size(mb),
+                    Err(_) => println!("info string wrong value {}", value),
+                }
+            }
+            "ponder" => {}
+            _ => println!("info string unknown option {}", name),
+        }
+    }
+
+    fn position(&mut self, tokens: &[&str]) {
+        let moves_start = tokens.iter().position(|&t| t == "moves").unwrap_or(tokens.len());
+        let board = match tokens.first() {
+            Some(&"startpos") => Ok(Board::new_variant(self.variant)),
+            Some(&"fen") => {
+                Board::from_fen_variant(&tokens[1..moves_start].join(" "), self.variant)
+            }
+            _ => {
+                println!("info string expected startpos or fen");
+                return;
+            }
+        };
+        let mut board = match board {
+            Ok(b) => b,
+            Err(e) => {
+                println!("info string {}", e);
+                return;
+            }
+        };
+        for s in tokens.iter().skip(moves_start + 1) {
+            match board.parse_uci_move(s, self.is_chess960()) {
+                Some(m) => board.do_move_inplace(m).unwrap(),
+                None => {
+                    println!("info string illegal move {}", s);
+                    return;
+                }
+            }
+        }
+        self.board = board;
+    }
+
+    fn go(&mut self, tokens: &[&str]) {
+        self.stop();
+        let params = GoParams::parse(tokens);
+        let limits = SearchLimits {
+            depth: params.depth,
+            nodes: params.nodes,
+            movetime: params.movetime.map(Duration::from_millis),
+            time_control: params.time_control(self.board.get_side_to_move()),
+            move_overhead: self.move_overhead,
+        };
+        let mut search = self.search.take().unwrap();
+        let board = self.board.clone();
+        let chess960 = self.is_chess960();
+        let stop = self.stop.clone();
+        let ponder = self.ponder.clone();
+        let infinite = params.infinite ||
+                       (limits.depth.is_none() && limits.nodes.is_none() &&
+                        limits.movetime.is_none() && limits.time_control.is_none());
+        self.infinite = infinite;
+        stop.store(false, Ordering::SeqCst);
+        ponder.store(params.ponder, Ordering::SeqCst);
+        self.worker = Some(thread::spawn(move || {
+            let result = search.run(&board, &limits, |info| print_info(&board, info, chess960));
+            // in infinite and ponder mode the best move must not be sent before `stop`
+            while !stop.load(Ordering::SeqCst) &&
+                  (infinite || ponder.load(Ordering::SeqCst)) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            print_bestmove(&board, &result, chess960);
+            search
+        }));
+    }
+}
+
+fn print_info(board: &Board, info: &SearchInfo, chess960: bool) {
+    let score = match info.score {
+        SearchScore::Centipawns(cp) => format!("cp {}", cp),
+        SearchScore::Mate(n) => format!("mate {}", n),
+    };
+    let time = info.time.as_secs() * 1000 + info.time.subsec_nanos() as u64 / 1_000_000;
+    println!("info depth {} seldepth {} multipv {} score {} nodes {} nps {} hashfull {} time {} \
+              pv {}",
+             info.depth,
+             info.seldepth,
+             info.multipv,
+             score,
+             info.nodes,
+             info.nps(),
+             info.hashfull,
+             time,
+             line_to_uci(board, &info.pv, chess960));
+}
+
+fn print_bestmove(board: &Board, result: &SearchResult, chess960: bool) {
+    match result.best_move {
+        Some(m) => {
+            let mut line = format!("bestmove {}", board.move_to_uci(m, chess960));
+            if let Some(p) = result.ponder_move {
+                let after = board.do_move(m).unwrap();
+                line.push_str(&format!(" ponder {}", after.move_to_uci(p, chess960)));
+            }
+            println!("{}", line);
+        }
+        None => println!("bestmove 0000"),
+    }
+}
+
+fn line_to_uci(board: &Board, line: &[Move], chess960: bool) -> String {
+    let mut b = board.clone();
+    let mut res = Vec::new();
+    for &m in line {
+        res.push(b.move_to_uci(m, chess960));
+        b.do_move_inplace(m).unwrap();
+    }
+    res.join(" ")
+}
+
+fn main() {
+    let mut engine = Engine::new();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        let line = match lines.next() {
+            Some(Ok(l)) => l,
+            _ => {
+                // end of a piped session: let a limited search finish
+                engine.ponder.store(false, Ordering::SeqCst);
+                if !engine.infinite {
+                    engine.wait();
+                }
+                break;
+            }
+        };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let (command, args) = match tokens.split_first() {
+            Some((c, a)) => (*c, a),
+            None => continue,
+        };
+        match command {
+            "uci" => {
+                println!("id name rusty-board");
+                println!("id author hyst329");
+                println!("option name Threads type spin default 1 min 1 max 256");
+                println!("option name Hash type spin default {} min 1 max 65536", DEFAULT_HASH_MB);
+                println!("option name MultiPV type spin default 1 min 1 max 500");
+                println!("option name Ponder type check default false");
+                println!("option name Move Overhead type spin default 30 min 0 max 5000");
+                println!("option name UCI_Chess960 type check default false");
+                let names: Vec<String> =
+                    variants().iter().map(|v| format!("var {}", v.name())).collect();
+                println!("option name UCI_Variant type combo default {} {}",
+                         STANDARD.name(),
+                         names.join(" "));
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "setoption" => {
+                engine.stop();
+                engine.set_option(args);
+            }
+            "ucinewgame" => {
+                engine.stop();
+                engine.board = Board::new_variant(engine.variant);
+                engine.search.as_mut().unwrap().clear_hash();
+            }
+            "position" => {
+                engine.stop();
+                engine.position(args);
+            }
+            "go" => engine.go(args),
+            "stop" => engine.stop(),
+            "ponderhit" => engine.ponder.store(false, Ordering::SeqCst),
+            "quit" => break,
+            _ => println!("info string unknown command {}", command),
+        }
+    }
+    engine.stop();
+}
@@ -0,0 +1,403 @@
+extern crate rusty_board;
+
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use rusty_board::board::*;
+use rusty_board::generator::*;
+use rusty_board::moves::*;
+use rusty_board::search::*;
+use rusty_board::square::*;
+use rusty_board::timeman::*;
+use rusty_board::variant::*;
+
+enum Event {
+    Line(String),
+    /// Search result together with the number of the search it belongs to.
+    Done(u32, SearchResult, Box<Search>),
+    Eof,
+}
+
+struct Engine {
+    board: Board,
+    variant: &'static dyn Variant,
+    force: bool,
+    engine_color: Color,
+    post: bool,
+    /// Time control from `level`: moves per session (0 for the whole game), base and increment.
+    moves_per_session: u32,
+    base_time: Duration,
+    increment: Duration,
+    /// Fixed time per move from `st`.
+    time_per_move: Option<Duration>,
+    max_depth: Option<u32>,
+    /// Engine's remaining time from `time`.
+    clock: Option<Duration>,
+    search: Option<Search>,
+    stop: Arc<AtomicBool>,
+    search_id: u32,
+    /// `think()` was called while an aborted search was still running.
+    think_pending: bool,
+    events: Sender<Event>,
+    /// Settings applied to the searcher before the next search.
+    threads: usize,
+    hash_size: Option<usize>,
+    clear_hash: bool,
+}
+
+impl Engine {
+    fn new(events: Sender<Event>) -> Engine {
+        let search = Search::new();
+        Engine {
+            board: Board::new(),
+            variant: &STANDARD,
+            force: false,
+            engine_color: Color::Black,
+            post: false,
+            moves_per_session: 0,
+            base_time: Duration::from_secs(300),
+            increment: Duration::from_secs(0),
+            time_per_move: None,
+            max_depth: None,
+            clock: None,
+            stop: search.stop_handle(),
+            search: Some(search),
+            search_id: 0,
+            think_pending: false,
+            events,
+            threads: 1,
+            hash_size: None,
+            clear_hash: false,
+        }
+    }
+
+    fn is_thinking(&self) -> bool {
+        self.search.is_none()
+    }
+
+    /// Aborts the running search, its result will be ignored.
+    fn abort(&mut self) {
+        self.think_pending = false;
+        if self.is_thinking() {
+            self.stop.store(true, Ordering::SeqCst);
+            self.search_id += 1;
+        }
+    }
+
+    fn time_control(&self) -> TimeControl {
+        let moves_to_go = if self.moves_per_session > 0 {
+            let played = (self.board.get_move_number() as u32 - 1) % self.moves_per_session;
+            Some(self.moves_per_session - played)
+        } else {
+            None
+        };
+        TimeControl {
+            remaining: self.clock.unwrap_or(self.base_time),
+            increment: if self.increment > Duration::from_secs(0) {
+                Increment::Fischer(self.increment)
+            } else {
+                Increment::None
+            },
+            moves_to_go,
+        }
+    }
+
+    fn think(&mut self) {
+        if self.is_thinking() {
+            // the searcher is busy until the aborted search comes back
+            self.think_pending = true;
+            return;
+        }
+        if self.game_over() {
+            return;
+        }
+        let mut search = self.search.take().unwrap();
+        search.set_threads(self.threads);
+        if let Some(mb) = self.hash_size.take() {
+            search.set_hash_size(mb);
+        } else if self.clear_hash {
+            search.clear_hash();
+        }
+        self.clear_hash = false;
+        let board = self.board.clone();
+        let limits = SearchLimits {
+            depth: self.max_depth,
+            nodes: None,
+            movetime: self.time_per_move,
+            time_control: Some(self.time_control()),
+            move_overhead: Duration::from_millis(30),
+        };
+        let post = self.post;
+        let id = self.search_id;
+        let events = self.events.clone();
+        self.stop.store(false, Ordering::SeqCst);
+        thread::spawn(move || {
+            let result = search.run(&board, &limits, |info| if post {
+                print_thinking(&board, info);
+            });
+            events.send(Event::Done(id, result, Box::new(search))).ok();
+        });
+    }
+
+    fn search_done(&mut self, id: u32, result: SearchResult, search: Box<Search>) {
+        self.search = Some(*search);
+        if id != self.search_id {
+            if self.think_pending {
+                self.think_pending = false;
+                self.think();
+            }
+            return;
+        }
+        self.search_id += 1;
+        self.think_pending = false;
+        if let Some(m) = result.best_move {
+            println!("move {}", move_to_cecp(&self.board, m, self.variant.is_chess960()));
+            self.board.do_move_inplace(m).unwrap();
+            self.game_over();
+        }
+    }
+
+    /// Prints the result if the game has ended.
+    fn game_over(&self) -> bool {
+        let result = match self.board.outcome() {
+            None => return false,
+            Some(Outcome::Checkmate(Color::White)) => "1-0 {White mates}",
+            Some(Outcome::Checkmate(Color::Black)) => "0-1 {Black mates}",
+            Some(Outcome::Stalemate) => "1/2-1/2 {Stalemate}",
+            Some(Outcome::FiftyMoveRule) => "1/2-1/2 {Draw by fifty move rule}",
+            Some(Outcome::ThreefoldRepetition) => "1/2-1/2 {Draw by repetition}",
+            Some(Outcome::InsufficientMaterial) => "1/2-1/2 {Insufficient material}",
+            Some(Outcome::VariantWin(Color::White)) => "1-0 {White wins}",
+            Some(Outcome::VariantWin(Color::Black)) => "0-1 {Black wins}",
+            Some(Outcome::VariantDraw) => "1/2-1/2 {Draw}",
+        };
+        println!("{}", result);
+        true
+    }
+
+    fn user_move(&mut self, s: &str) {
+        self.abort();
+        match parse_cecp_move(&self.board, s, self.variant.is_chess960()) {
+            Some(m) => {
+                self.board.do_move_inplace(m).unwrap();
+                if !self.force && self.board.get_side_to_move() == self.engine_color {
+                    self.think();
+                } else {
+                    self.game_over();
+                }
+            }
+            None => println!("Illegal move: {}", s),
+        }
+    }
+
+    fn undo(&mut self, plies: u32) {
+        self.abort();
+        for _ in 0..plies {
+            if self.board.undo_move_inplace().is_err() {
+                println!("Error (nothing to undo): undo");
+                return;
+            }
+        }
+    }
+
+    fn level(&mut self, args: &[&str]) {
+        if args.len() != 3 {
+            println!("Error (wrong arguments): level");
+            return;
+        }
+        let mut base = args[1].split(':');
+        let minutes = base.next().and_then(|m| m.parse::<u64>().ok());
+        let seconds = base.next().map_or(Some(0), |s| s.parse::<u64>().ok());
+        match (args[0].parse(), minutes, seconds, args[2].parse::<f64>()) {
+            (Ok(mps), Some(m), Some(s), Ok(inc)) => {
+                self.moves_per_session = mps;
+                self.base_time = Duration::from_secs(m * 60 + s);
+                self.increment = Duration::from_millis((inc * 1000.0) as u64);
+                self.time_per_move = None;
+            }
+            _ => println!("Error (wrong arguments): level"),
+        }
+    }
+
+    /// Handles a command, returns `false` on `quit`.
+    fn command(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let (command, args) = match tokens.split_first() {
+            Some((c, a)) => (*c, a),
+            None => return true,
+        };
+        match command {
+            "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer" |
+            "name" | "rating" | "ics" | "result" | "otim" => {}
+            "protover" => {
+                let names: Vec<&str> = variants().iter().map(|v| v.xboard_name()).collect();
+                println!("feature myname=\"rusty-board\" ping=1 setboard=1 usermove=1 san=0 \
+                          colors=0 analyze=0 sigint=0 sigterm=0 smp=1 memory=1 \
+                          variants=\"{}\"",
+                         names.join(","));
+                println!("feature done=1");
+            }
+            "new" => {
+                self.abort();
+                self.board = Board::new();
+                self.variant = &STANDARD;
+                self.force = false;
+                self.engine_color = Color::Black;
+                self.max_depth = None;
+                self.clock = None;
+                self.clear_hash = true;
+            }
+            "variant" => {
+                self.abort();
+                match args.first().and_then(|name| variant_by_name(name)) {
+                    Some(v) => {
+                        self.variant = v;
+                        self.board = Board::new_variant(v);
+                    }
+                    None => println!("Error (unsupported variant): {}", line),
+                }
+            }
+            "setboard" => {
+                self.abort();
+                match Board::from_fen_variant(&args.join(" "), self.variant) {
+                    Ok(b) => self.board = b,
+                    Err(e) => println!("tellusererror Illegal position: {}", e),
+                }
+            }
+            "usermove" => {
+                match args.first() {
+                    Some(s) => self.user_move(s),
+                    None => println!("Error (no move): usermove"),
+                }
+            }
+            "go" => {
+                self.force = false;
+                self.engine_color = self.board.get_side_to_move();
+                self.think();
+            }
+            "force" => {
+                self.abort();
+                self.force = true;
+            }
+            "playother" => {
+                self.force = false;
+                self.engine_color = self.board.get_side_to_move().opposite();
+            }
+            "?" => self.stop.store(true, Ordering::SeqCst),
+            "undo" => self.undo(1),
+            "remove" => self.undo(2),
+            "level" => self.level(args),
+            "st" => {
+                match args.first().and_then(|s| s.parse::<f64>().ok()) {
+                    Some(t) => self.time_per_move = Some(Duration::from_millis((t * 1000.0) as u64)),
+                    None => println!("Error (wrong arguments): st"),
+                }
+            }
+            "sd" => self.max_depth = args.first().and_then(|s| s.parse().ok()),
+            "time" => {
+                // in centiseconds, negative once the engine has lost on time
+                self.clock = args.first()
+                    .and_then(|s| s.parse::<i64>().ok())
+                    .map(|cs| Duration::from_millis(cs.max(0) as u64 * 10));
+            }
+            "cores" => {
+                match args.first().and_then(|s| s.parse().ok()) {
+                    Some(n) => self.threads = n,
+                    None => println!("Error (wrong arguments): cores"),
+                }
+            }
+            "memory" => {
+                match args.first().and_then(|s| s.parse().ok()) {
+                    Some(mb) => self.hash_size = Some(mb),
+                    None => println!("Error (wrong arguments): memory"),
+                }
+            }
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "ping" => println!("pong {}", args.first().unwrap_or(&"")),
+            "quit" => return false,
+            _ => println!("Error (unknown command): {}", command),
+        }
+        true
+    }
+}
+
+/// Mate scores are written as 100000 + N for a mate in N moves.
+fn print_thinking(board: &Board, info: &SearchInfo) {
+    let score = match info.score {
+        SearchScore::Centipawns(cp) => cp,
+        SearchScore::Mate(n) if n > 0 => 100000 + n,
+        SearchScore::Mate(n) => -100000 + n,
+    };
+    let centiseconds = info.time.as_secs() * 100 + info.time.subsec_nanos() as u64 / 10_000_000;
+    let mut b = board.clone();
+    let mut pv = Vec::new();
+    for &m in &info.pv {
+        pv.push(move_to_cecp(&b, m, false));
+        b.do_move_inplace(m).unwrap();
+    }
+    println!("{} {} {} {} {}", info.depth, score, centiseconds, info.nodes, pv.join(" "));
+}
+
+/// Moves are sent in coordinate notation, Fischer Random castlings as `O-O`/`O-O-O`.
+fn move_to_cecp(board: &Board, m: Move, chess960: bool) -> String {
+    if chess960 && m.is_castling() {
+        return if m.get_square_to().get_file() == File::G { "O-O" } else { "O-O-O" }.to_string();
+    }
+    board.move_to_uci(m, false)
+}
+
+fn parse_cecp_move(board: &Board, s: &str, chess960: bool) -> Option<Move> {
+    let castling = match s {
+        "O-O" | "0-0" => Some(File::G),
+        "O-O-O" | "0-0-0" => Some(File::C),
+        _ => None,
+    };
+    if let Some(file) = castling {
+        return generate_legal_moves(board.clone())
+            .into_iter()
+            .find(|m| m.is_castling() && m.get_square_to().get_file() == file);
+    }
+    board.parse_uci_move(s, chess960).or_else(|| board.parse_uci_move(s, false))
+}
+
+fn main() {
+    let (sender, receiver) = channel();
+    let lines = sender.clone();
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+            match line {
+                Ok(l) => {
+                    if lines.send(Event::Line(l)).is_err() {
+                        return;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        lines.send(Event::Eof).ok();
+    });
+    let mut engine = Engine::new(sender);
+    let mut eof = false;
+    for event in receiver.iter() {
+        match event {
+            Event::Line(l) => {
+                if !engine.command(&l) {
+                    break;
+                }
+            }
+            Event::Done(id, result, search) => engine.search_done(id, result, search),
+            Event::Eof => eof = true,
+        }
+        // end of a piped session: finish thinking first
+        if eof && !engine.is_thinking() {
+            break;
+        }
+    }
+    engine.abort();
+}
@@ -1,8 +1,16 @@
+use std::cmp::max;
+
 use ::square::*;
 use ::piece::*;
 use ::moves::*;
+use ::error::*;
+use ::generator::*;
+use ::chess960;
+use ::variant::*;
+use ::zobrist::KEYS;
 
 /// Represents different castlings for different sides
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum CastlingRights {
     /// White can castle kingside
     WhiteKingside,
@@ -14,6 +22,32 @@ pub enum CastlingRights {
     BlackQueenside,
 }
 
+/// The way a game has ended
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// The given side has mated its opponent
+    Checkmate(Color),
+    Stalemate,
+    FiftyMoveRule,
+    ThreefoldRepetition,
+    /// Neither side can mate
+    InsufficientMaterial,
+    /// The given side has won by a rule of the variant (like exploding the enemy king)
+    VariantWin(Color),
+    /// Drawn by a rule of the variant
+    VariantDraw,
+}
+
+impl Outcome {
+    /// Returns the winning side, or `None` for a draw.
+    pub fn winner(&self) -> Option<Color> {
+        match *self {
+            Outcome::Checkmate(c) | Outcome::VariantWin(c) => Some(c),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone)]
 /// The main data type for chessboard
 pub struct Board {
@@ -25,116 +59,169 @@ pub struct Board {
     white_king: Square,
     black_king: Square,
     castling: [bool; 4],
-    initial_king_file: File,
-    initial_queens_rook_file: File,
-    initial_kings_rook_file: File,
+    /// Initial files, indexed by color (they differ in Double Chess960).
+    initial_king_file: [File; 2],
+    initial_queens_rook_file: [File; 2],
+    initial_kings_rook_file: [File; 2],
     move_list: Vec<Move>,
+    history: Vec<BoardState>,
+    hash: u64,
+    variant: &'static dyn Variant,
+    /// Dimensions given by the variant, cached to avoid the dynamic call.
+    geometry: Geometry,
+    /// Pieces in hand, indexed by color and kind (Crazyhouse).
+    pockets: [[u8; KIND_COUNT]; 2],
+    /// Squares of promoted pieces, one bit per square index.
+    promoted: u128,
+    /// Checks each side still has to give to win (Three-check).
+    checks_remaining: [u8; 2],
+}
+
+/// The part of the board state that can't be restored from the move itself.
+#[derive(Clone, Copy)]
+struct BoardState {
+    castling: [bool; 4],
+    en_passant_square: Option<Square>,
+    halfmove_count: u16,
+    hash: u64,
+    pockets: [[u8; KIND_COUNT]; 2],
+    promoted: u128,
+    checks_remaining: [u8; 2],
+    /// Pieces around the target square removed by an explosion (Atomic),
+    /// in the order of `NEIGHBOURS`.
+    exploded: [Option<Piece>; 8],
+}
+
+/// Directions to the squares around a square.
+const NEIGHBOURS: [Direction; 8] = [Direction::Up, Direction::UpRight, Direction::Right,
+                                    Direction::DownRight, Direction::Down, Direction::DownLeft,
+                                    Direction::Left, Direction::UpLeft];
+
+impl Default for Board {
+    fn default() -> Board {
+        Board::new()
+    }
 }
 
 impl Board {
     /// Constructs a new chessboard with starting position.
     /// Equivalent to `Board::new_chess960(518)`.
     pub fn new() -> Board {
-        Board::new_chess960(518)
+        let mut b = Board::new_chess960(518);
+        b.variant = &STANDARD;
+        b
+    }
+
+    /// Constructs a new chessboard with the starting position of a variant.
+    pub fn new_variant(variant: &'static dyn Variant) -> Board {
+        variant.start_position()
     }
     /// Constructs a new chessboard with Chess960 starting position number `position_number`.
     /// Position numbers are treated modulo 960
     /// (i. e. #960, #961, #962 are equivalent to #0, #1, #2 etc.)
     pub fn new_chess960(position_number: u32) -> Board {
-        let n1 = position_number % 960;
-        let mut piece_order = [PieceKind::Pawn; 8];
-        let mut indices: Vec<usize> = (0..8).collect();
-        let n2 = n1 / 4;
-        let b1 = n1 % 4;
-        let n3 = n2 / 4;
-        let b2 = n2 % 4;
-        let b1idx = (2 * b1 + 1) as usize;
-        let b2idx = (2 * b2) as usize;
-        piece_order[b1idx] = PieceKind::Bishop;
-        piece_order[b2idx] = PieceKind::Bishop;
-        let b1pos = indices.iter().position(|&x| x == b1idx).unwrap();
-        indices.remove(b1pos);
-        let b2pos = indices.iter().position(|&x| x == b2idx).unwrap();
-        indices.remove(b2pos);
-        let n4 = n3 / 6;
-        let q = n3 % 6;
-        let qidx = indices[q as usize];
-        indices.remove(q as usize);
-        piece_order[qidx] = PieceKind::Queen;
-        let kts = match n4 {
-            0 => (0, 1),
-            1 => (0, 2),
-            2 => (0, 3),
-            3 => (0, 4),
-            4 => (1, 2),
-            5 => (1, 3),
-            6 => (1, 4),
-            7 => (2, 3),
-            8 => (2, 4),
-            9 => (3, 4),
-            _ => unreachable!(),
-        };
-        piece_order[indices[kts.0]] = PieceKind::Knight;
-        piece_order[indices[kts.1]] = PieceKind::Knight;
-        indices.remove(kts.0);
-        indices.remove(kts.1 - 1); // vector gets shorter, so we must subtract one
-        piece_order[indices[0]] = PieceKind::Rook;
-        piece_order[indices[1]] = PieceKind::King;
-        piece_order[indices[2]] = PieceKind::Rook;
-        let mut board: [Option<Piece>; 64] = [None; 64];
-        for i in 0..8 {
-            board[i] = Some(Piece::new(piece_order[i], Color::White));
-            board[8 + i] = Some(Piece::new(PieceKind::Pawn, Color::White));
-            board[48 + i] = Some(Piece::new(PieceKind::Pawn, Color::Black));
-            board[56 + i] = Some(Piece::new(piece_order[i], Color::Black));
-        }
-        let qr_file = match indices[0] {
-            0 => File::A,
-            1 => File::B,
-            2 => File::C,
-            3 => File::D,
-            4 => File::E,
-            5 => File::F,
-            6 => File::G,
-            7 => File::H,
-            _ => unreachable!(),
-        };
-        let k_file = match indices[1] {
-            0 => File::A,
-            1 => File::B,
-            2 => File::C,
-            3 => File::D,
-            4 => File::E,
-            5 => File::F,
-            6 => File::G,
-            7 => File::H,
-            _ => unreachable!(),
-        };
-        let kr_file = match indices[2] {
-            0 => File::A,
-            1 => File::B,
-            2 => File::C,
-            3 => File::D,
-            4 => File::E,
-            5 => File::F,
-            6 => File::G,
-            7 => File::H,
-            _ => unreachable!(),
-        };
-        Board {
-            board: board.to_vec(),
+        Board::new_double_chess960(position_number, position_number)
+    }
+
+    /// Constructs a new chessboard for Double Fischer Random Chess (Chess960²),
+    /// where White and Black get independent Chess960 setups.
+    pub fn new_double_chess960(white_position_number: u32, black_position_number: u32) -> Board {
+        let orders = [chess960::back_rank(white_position_number),
+                      chess960::back_rank(black_position_number)];
+        Board::from_back_ranks([&orders[0], &orders[1]], &CHESS960)
+    }
+
+    /// Constructs a new 10x8 chessboard with Capablanca Random starting position number
+    /// `position_number` (see `chess960::capablanca_back_rank()`).
+    pub fn new_capablanca_random(position_number: u32) -> Board {
+        let order = chess960::capablanca_back_rank(position_number);
+        Board::from_back_ranks([&order, &order], &CAPABLANCA_RANDOM)
+    }
+
+    /// Sets up the given back ranks with pawns in front of them and castling rights
+    /// for the king and the two rooks.
+    fn from_back_ranks(orders: [&[PieceKind]; 2], variant: &'static dyn Variant) -> Board {
+        let geometry = variant.geometry();
+        let mut board = vec![None; SQUARE_COUNT];
+        for &color in &[Color::White, Color::Black] {
+            for (f, &kind) in orders[color as usize].iter().enumerate() {
+                let file = File::from_int(f as u32);
+                let back = Square::from_file_and_rank(file, geometry.back_rank(color));
+                let front = Square::from_file_and_rank(file, geometry.relative_rank(color, 1));
+                board[back.as_index()] = Some(Piece::new(kind, color));
+                board[front.as_index()] = Some(Piece::new(PieceKind::Pawn, color));
+            }
+        }
+        let file_of = |color: Color, kind: PieceKind, nth: usize| {
+            let order = orders[color as usize];
+            let i = (0..order.len()).filter(|&i| order[i] == kind).nth(nth).unwrap();
+            File::from_int(i as u32)
+        };
+        let files = |kind: PieceKind, nth: usize| {
+            [file_of(Color::White, kind, nth), file_of(Color::Black, kind, nth)]
+        };
+        let k_file = files(PieceKind::King, 0);
+        let mut b = Board {
+            board,
             side_to_move: Color::White,
             move_number: 1,
             halfmove_count: 0,
             en_passant_square: None,
-            white_king: Square::from_file_and_rank(k_file, Rank::First),
-            black_king: Square::from_file_and_rank(k_file, Rank::Eighth),
+            white_king: Square::from_file_and_rank(k_file[0], geometry.back_rank(Color::White)),
+            black_king: Square::from_file_and_rank(k_file[1], geometry.back_rank(Color::Black)),
             castling: [true; 4],
             initial_king_file: k_file,
-            initial_queens_rook_file: qr_file,
-            initial_kings_rook_file: kr_file,
+            initial_queens_rook_file: files(PieceKind::Rook, 0),
+            initial_kings_rook_file: files(PieceKind::Rook, 1),
             move_list: Vec::new(),
+            history: Vec::new(),
+            hash: 0,
+            variant,
+            geometry,
+            pockets: [[0; KIND_COUNT]; 2],
+            promoted: 0,
+            checks_remaining: [3; 2],
+        };
+        b.hash = b.compute_hash();
+        b
+    }
+
+    /// Constructs a new chessboard with a random Chess960 starting position.
+    pub fn new_chess960_random(rng: &mut chess960::Random) -> Board {
+        Board::new_chess960(rng.position_number())
+    }
+
+    /// Returns the Chess960 position number if both back ranks hold the same
+    /// Chess960 setup (other squares are not checked).
+    pub fn chess960_position_number(&self) -> Option<u32> {
+        let geometry = self.get_geometry();
+        if geometry.get_files() != 8 {
+            return None;
         }
+        let mut rank = [PieceKind::Pawn; 8];
+        for (i, kind) in rank.iter_mut().enumerate() {
+            let file = File::from_int(i as u32);
+            let white = self.get_piece(Square::from_file_and_rank(file,
+                                                                  geometry.back_rank(Color::White)))?;
+            let black = self.get_piece(Square::from_file_and_rank(file,
+                                                                  geometry.back_rank(Color::Black)))?;
+            if white.get_color() != Color::White ||
+               black != Piece::new(white.get_kind(), Color::Black) {
+                return None;
+            }
+            *kind = white.get_kind();
+        }
+        chess960::position_number(&rank)
+    }
+
+    /// Returns the rules this board is played by.
+    pub fn get_variant(&self) -> &'static dyn Variant {
+        self.variant
+    }
+
+    /// Returns the dimensions of the board (given by the variant).
+    pub fn get_geometry(&self) -> Geometry {
+        self.geometry
     }
 
     /// Returns piece on a given square or `None` if the square is empty.
@@ -151,82 +238,1234 @@ impl Board {
         self.en_passant_square
     }
 
+    /// Returns the number of the current full move (starting from 1).
+    pub fn get_move_number(&self) -> u16 {
+        self.move_number
+    }
+
+    /// Returns the number of halfmoves since the last capture or pawn move.
+    pub fn get_halfmove_count(&self) -> u16 {
+        self.halfmove_count
+    }
+
+    /// Returns the moves made on this board, oldest first.
+    pub fn get_move_list(&self) -> &[Move] {
+        &self.move_list
+    }
+
+    /// Returns the square the king of a given color stands on.
+    pub fn get_king_square(&self, color: Color) -> Square {
+        match color {
+            Color::White => self.white_king,
+            Color::Black => self.black_king,
+        }
+    }
+
+    /// Returns `true` if the king of the given color is on the board
+    /// (it may be missing in variants).
+    pub fn has_king(&self, color: Color) -> bool {
+        self.get_piece(self.get_king_square(color)) == Some(Piece::new(PieceKind::King, color))
+    }
+
+    /// Returns `true` if the given castling is still allowed.
+    pub fn can_castle(&self, castling: CastlingRights) -> bool {
+        self.castling[castling as usize]
+    }
+
+    /// Returns the file the king of the given color starts on (E for standard chess).
+    pub fn get_initial_king_file(&self, color: Color) -> File {
+        self.initial_king_file[color as usize]
+    }
+
+    /// Returns the file the queenside rook of the given color starts on (A for standard chess).
+    pub fn get_initial_queens_rook_file(&self, color: Color) -> File {
+        self.initial_queens_rook_file[color as usize]
+    }
+
+    /// Returns the file the kingside rook of the given color starts on (H for standard chess).
+    pub fn get_initial_kings_rook_file(&self, color: Color) -> File {
+        self.initial_kings_rook_file[color as usize]
+    }
+
+    /// Returns how many pieces of a kind the given side holds in its pocket (Crazyhouse).
+    pub fn get_pocket(&self, color: Color, kind: PieceKind) -> u8 {
+        self.pockets[color as usize][kind as usize]
+    }
+
+    /// Returns `true` if the piece on the square is a promoted pawn
+    /// (it goes back into a pocket as a pawn when captured in Crazyhouse).
+    pub fn is_promoted(&self, s: Square) -> bool {
+        self.promoted & 1 << s.as_index() != 0
+    }
+
+    /// Puts a piece into a pocket, for FEN parsing and piece transfers between boards.
+    /// Fails if the pocket can't count any more pieces of the kind.
+    pub(crate) fn add_to_pocket(&mut self,
+                                color: Color,
+                                kind: PieceKind)
+                                -> Result<(), BoardError> {
+        let count = &mut self.pockets[color as usize][kind as usize];
+        let more = count.checked_add(1)
+            .ok_or(BoardError::InvalidPosition("Too many pieces in a pocket"))?;
+        let piece = Piece::new(kind, color);
+        self.hash ^= KEYS.pocket(piece, *count) ^ KEYS.pocket(piece, more);
+        *count = more;
+        Ok(())
+    }
+
+    /// Marks the piece on a square as promoted, for FEN parsing.
+    pub(crate) fn set_promoted(&mut self, s: Square) {
+        self.promoted |= 1 << s.as_index();
+    }
+
+    /// Returns how many more checks the given side has to give to win (Three-check).
+    pub fn get_checks_remaining(&self, color: Color) -> u8 {
+        self.checks_remaining[color as usize]
+    }
+
+    /// Sets the number of checks left, for FEN parsing.
+    pub(crate) fn set_checks_remaining(&mut self, color: Color, remaining: u8) {
+        self.hash ^= KEYS.checks(color, self.checks_remaining[color as usize]) ^
+                     KEYS.checks(color, remaining);
+        self.checks_remaining[color as usize] = remaining;
+    }
+
+    /// Returns `true` if `m` is a pseudo-legal move in this position: it follows the rules
+    /// of the moving piece, but may leave the king in check. Unlike generating the moves,
+    /// it is fast enough to validate moves from the transposition table or killer moves.
+    pub fn is_pseudo_legal(&self, m: Move) -> bool {
+        self.variant.is_pseudo_legal(self, m)
+    }
+
+    /// Returns `true` if `m` is a legal move in this position. The move is made and undone
+    /// on the board, which is left as it was.
+    pub fn is_legal(&mut self, m: Move) -> bool {
+        if !self.is_pseudo_legal(m) || self.variant.special_outcome(self).is_some() ||
+           self.do_move_inplace(m).is_err() {
+            return false;
+        }
+        let legal = self.variant.is_legal(self, m);
+        self.undo_move_inplace().unwrap();
+        legal
+    }
+
+    /// Checks that the pieces on the board match the move,
+    /// returns the square of the castling rook.
+    fn check_move(&self, m: Move) -> Result<Option<Square>, MoveError> {
+        let from = m.get_square_from();
+        let to = m.get_square_to();
+        let p = m.get_moving_piece();
+        let color = p.get_color();
+        let geometry = self.get_geometry();
+        if m.is_drop() {
+            if self.get_piece(to).is_some() {
+                return Err(MoveError::OccupiedSquare(m, to));
+            }
+            if self.get_pocket(color, p.get_kind()) == 0 {
+                return Err(MoveError::NotInPocket(m));
+            }
+        } else if self.get_piece(from).is_none() {
+            return Err(MoveError::EmptySquare(m, from));
+        } else if self.get_piece(from) != Some(p) {
+            return Err(MoveError::WrongMovingPiece(m, from));
+        }
+        if m.is_en_passant() {
+            let captured_square = to.get_back_in(color, geometry)
+                .ok_or(MoveError::WrongCapturedPiece(m, to))?;
+            if self.get_piece(captured_square) != m.get_captured_piece() {
+                return Err(MoveError::WrongCapturedPiece(m, captured_square));
+            }
+        } else if !m.is_castling() && self.get_piece(to) != m.get_captured_piece() {
+            return Err(MoveError::WrongCapturedPiece(m, to));
+        }
+        if m.is_castling() {
+            let (rook_from, _) = self.castling_rook_squares(color, is_kingside(m));
+            if p.get_kind() != PieceKind::King ||
+               self.get_piece(rook_from) != Some(Piece::new(PieceKind::Rook, color)) {
+                return Err(MoveError::WrongCastling(m));
+            }
+            Ok(Some(rook_from))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Makes a move on the board. Returns `Ok(())` if the move succeeds and `Err` otherwise.
+    /// Only the moving and the captured pieces are checked, see `do_move_inplace_checked()`.
     /// This method _modifies_ the original board.
-    pub fn do_move_inplace(&mut self, m: Move) -> Result<(), &'static str> {
+    pub fn do_move_inplace(&mut self, m: Move) -> Result<(), MoveError> {
+        let castling_rook = self.check_move(m)?;
         let from = m.get_square_from();
         let to = m.get_square_to();
-        if let None = self.get_piece(from) {
-            return Err("Empty starting square");
+        let p = m.get_moving_piece();
+        let color = p.get_color();
+        let geometry = self.get_geometry();
+        self.history.push(BoardState {
+            castling: self.castling,
+            en_passant_square: self.en_passant_square,
+            halfmove_count: self.halfmove_count,
+            hash: self.hash,
+            pockets: self.pockets,
+            promoted: self.promoted,
+            checks_remaining: self.checks_remaining,
+            exploded: [None; 8],
+        });
+        let old_side = self.side_to_move;
+        let captured_square = if m.is_en_passant() {
+            to.get_back_in(color, geometry).unwrap()
+        } else {
+            to
+        };
+        if let Some(captured) = m.get_captured_piece() {
+            if self.variant.captures_to_pocket() {
+                let kind = if self.is_promoted(captured_square) {
+                    PieceKind::Pawn
+                } else {
+                    captured.get_kind()
+                };
+                self.pockets[color as usize][kind as usize] += 1;
+            }
         }
-        let p = self.get_piece(from).unwrap();
-        if p != m.get_moving_piece() {
-            return Err("Wrong moving piece");
+        let was_promoted = !m.is_drop() && self.is_promoted(from);
+        self.promoted &= !(1 << from.as_index() | 1 << captured_square.as_index());
+        if was_promoted || m.get_promoted_to().is_some() {
+            self.promoted |= 1 << to.as_index();
         }
-        if self.get_piece(to) != m.get_captured_piece() && !m.is_en_passant() {
-            return Err("Wrong captured piece");
+        if m.is_drop() {
+            self.pockets[color as usize][p.get_kind() as usize] -= 1;
         }
-        if m.is_normal_move() {
-            self.board[from.as_index()] = None;
-            self.board[to.as_index()] = if m.get_promoted_to().is_some() {
-                Some(Piece::new(m.get_promoted_to().unwrap(), p.get_color()))
-            } else {
-                Some(p)
-            };
-            if m.get_moving_piece().get_kind() == PieceKind::Pawn &&
-                (from.as_index() as i16 - to.as_index() as i16).abs() == 16 {
-                let square_index = (from.as_index() + to.as_index()) / 2;
-                self.en_passant_square = Some(Square::from_int(square_index as u32));
+        self.board[from.as_index()] = None;
+        if let Some(rook_from) = castling_rook {
+            let (_, rook_to) = self.castling_rook_squares(color, is_kingside(m));
+            self.board[rook_from.as_index()] = None;
+            self.board[rook_to.as_index()] = Some(Piece::new(PieceKind::Rook, color));
+        }
+        if m.is_en_passant() {
+            self.board[captured_square.as_index()] = None;
+        }
+        self.board[to.as_index()] = match m.get_promoted_to() {
+            Some(kind) => Some(Piece::new(kind, color)),
+            None => Some(p),
+        };
+        self.en_passant_square = None;
+        // double steps from the back rank (Horde) don't allow en passant
+        if p.get_kind() == PieceKind::Pawn && from.get_rank() != geometry.back_rank(color) &&
+           (from.get_rank() as i32 - to.get_rank() as i32).abs() == 2 {
+            self.en_passant_square = from.get_forward_in(color, geometry);
+        }
+        if p.get_kind() == PieceKind::King {
+            match color {
+                Color::White => self.white_king = to,
+                Color::Black => self.black_king = to,
             }
         }
-        if m.is_castling() {
-            //TODO: Castling move
+        self.update_castling_rights(from);
+        self.update_castling_rights(to);
+        if self.explodes(m) {
+            self.explode(to);
         }
-        if m.is_en_passant() {
-            //TODO: En passant move
+        if p.get_kind() == PieceKind::Pawn || m.get_captured_piece().is_some() {
+            self.halfmove_count = 0;
+        } else {
+            self.halfmove_count += 1;
+        }
+        if color == Color::Black {
+            self.move_number += 1;
+        }
+        self.side_to_move = color.opposite();
+        if self.variant.counts_checks() && self.is_in_check() {
+            let remaining = &mut self.checks_remaining[color as usize];
+            *remaining = remaining.saturating_sub(1);
         }
         self.move_list.push(m);
+        self.update_hash(m);
+        if old_side == self.side_to_move {
+            self.hash ^= KEYS.side();
+        }
+        debug_assert_eq!(self.hash, self.compute_hash());
         Ok(())
     }
 
+    /// Returns `true` if the move makes an explosion (captures in Atomic).
+    fn explodes(&self, m: Move) -> bool {
+        m.get_captured_piece().is_some() && self.variant.explodes()
+    }
+
+    /// Removes the piece on `center` and all pieces but pawns around it,
+    /// saving them for `undo_move_inplace()`.
+    fn explode(&mut self, center: Square) {
+        let geometry = self.get_geometry();
+        let mut exploded = [None; 8];
+        for (i, &d) in NEIGHBOURS.iter().enumerate() {
+            let s = match center.get_by_dir_in(d, geometry) {
+                Some(s) => s,
+                None => continue,
+            };
+            match self.board[s.as_index()] {
+                Some(p) if p.get_kind() != PieceKind::Pawn => {
+                    exploded[i] = Some(p);
+                    self.board[s.as_index()] = None;
+                    self.promoted &= !(1 << s.as_index());
+                    self.update_castling_rights(s);
+                }
+                _ => {}
+            }
+        }
+        self.board[center.as_index()] = None;
+        self.promoted &= !(1 << center.as_index());
+        self.history.last_mut().unwrap().exploded = exploded;
+    }
+
+    /// Updates the hash after a move, comparing with the state saved before it.
+    fn update_hash(&mut self, m: Move) {
+        let old = *self.history.last().unwrap();
+        let geometry = self.get_geometry();
+        let p = m.get_moving_piece();
+        let color = p.get_color();
+        let to = m.get_square_to();
+        let mut h = self.hash ^ KEYS.side();
+        if !m.is_drop() {
+            h ^= KEYS.piece(p, m.get_square_from());
+        }
+        if m.is_castling() {
+            let (rook_from, rook_to) = self.castling_rook_squares(color, is_kingside(m));
+            let rook = Piece::new(PieceKind::Rook, color);
+            h ^= KEYS.piece(rook, rook_from) ^ KEYS.piece(rook, rook_to);
+        }
+        if let Some(captured) = m.get_captured_piece() {
+            let captured_square = if m.is_en_passant() {
+                to.get_back_in(color, geometry).unwrap()
+            } else {
+                to
+            };
+            h ^= KEYS.piece(captured, captured_square);
+        }
+        let placed = match m.get_promoted_to() {
+            Some(kind) => Piece::new(kind, color),
+            None => p,
+        };
+        h ^= KEYS.piece(placed, to);
+        if self.explodes(m) {
+            h ^= KEYS.piece(placed, to);
+            for (&d, p) in NEIGHBOURS.iter().zip(old.exploded.iter()) {
+                if let Some(p) = *p {
+                    h ^= KEYS.piece(p, to.get_by_dir_in(d, geometry).unwrap());
+                }
+            }
+        }
+        if let Some(s) = old.en_passant_square {
+            h ^= KEYS.en_passant(s);
+        }
+        if let Some(s) = self.en_passant_square {
+            h ^= KEYS.en_passant(s);
+        }
+        for (i, (&old, &new)) in old.castling.iter().zip(self.castling.iter()).enumerate() {
+            if old != new {
+                h ^= KEYS.castling(i);
+            }
+        }
+        if old.pockets != self.pockets {
+            h ^= pockets_hash(&old.pockets) ^ pockets_hash(&self.pockets);
+        }
+        for &c in &[Color::White, Color::Black] {
+            let (old, new) = (old.checks_remaining[c as usize], self.checks_remaining[c as usize]);
+            if old != new {
+                h ^= KEYS.checks(c, old) ^ KEYS.checks(c, new);
+            }
+        }
+        self.hash = h;
+    }
+
+    fn compute_hash(&self) -> u64 {
+        let mut h = 0;
+        for (i, p) in self.board.iter().enumerate() {
+            if let Some(p) = *p {
+                h ^= KEYS.piece(p, Square::from_index(i));
+            }
+        }
+        for (i, &c) in self.castling.iter().enumerate() {
+            if c {
+                h ^= KEYS.castling(i);
+            }
+        }
+        if let Some(s) = self.en_passant_square {
+            h ^= KEYS.en_passant(s);
+        }
+        if self.side_to_move == Color::Black {
+            h ^= KEYS.side();
+        }
+        for &c in &[Color::White, Color::Black] {
+            h ^= KEYS.checks(c, self.checks_remaining[c as usize]);
+        }
+        h ^ pockets_hash(&self.pockets)
+    }
+
+    /// Returns the Zobrist hash of the position.
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Returns `true` if the current position has already occurred in the game
+    /// (since the last capture or pawn move).
+    pub fn is_repetition(&self) -> bool {
+        self.repetitions() > 0
+    }
+
+    /// Counts earlier occurrences of the current position.
+    pub fn repetitions(&self) -> usize {
+        let len = self.history.len();
+        let plies = (self.halfmove_count as usize).min(len);
+        (2..plies + 1).step_by(2).filter(|&i| self.history[len - i].hash == self.hash).count()
+    }
+
+    /// Returns the squares the rook castling to a given side moves from and to.
+    fn castling_rook_squares(&self, color: Color, kingside: bool) -> (Square, Square) {
+        let rank = self.get_geometry().back_rank(color);
+        let rook_to = Square::from_file_and_rank(self.castling_files(kingside).1, rank);
+        if kingside {
+            (Square::from_file_and_rank(self.initial_kings_rook_file[color as usize], rank), rook_to)
+        } else {
+            (Square::from_file_and_rank(self.initial_queens_rook_file[color as usize], rank), rook_to)
+        }
+    }
+
+    /// Returns the files the king and the rook end up on after castling: G and F kingside,
+    /// C and D queenside on the standard board. On wider boards the kingside files
+    /// are counted from the last file (I and H on a 10x8 board).
+    pub(crate) fn castling_files(&self, kingside: bool) -> (File, File) {
+        if kingside {
+            let last = self.get_geometry().get_files() as u32 - 1;
+            (File::from_int(last - 1), File::from_int(last - 2))
+        } else {
+            (File::C, File::D)
+        }
+    }
+
+    /// Clears castling rights affected by a move from or to the square `s`.
+    fn update_castling_rights(&mut self, s: Square) {
+        for &color in &[Color::White, Color::Black] {
+            if s.get_rank() != self.get_geometry().back_rank(color) {
+                continue;
+            }
+            let (kingside, queenside) = match color {
+                Color::White => (CastlingRights::WhiteKingside, CastlingRights::WhiteQueenside),
+                Color::Black => (CastlingRights::BlackKingside, CastlingRights::BlackQueenside),
+            };
+            if s.get_file() == self.initial_king_file[color as usize] {
+                self.castling[kingside as usize] = false;
+                self.castling[queenside as usize] = false;
+            }
+            if s.get_file() == self.initial_kings_rook_file[color as usize] {
+                self.castling[kingside as usize] = false;
+            }
+            if s.get_file() == self.initial_queens_rook_file[color as usize] {
+                self.castling[queenside as usize] = false;
+            }
+        }
+    }
+
+    /// Makes a move on the board like `do_move_inplace()`, but rejects moves that aren't legal.
+    pub fn do_move_inplace_checked(&mut self, m: Move) -> Result<(), MoveError> {
+        if self.variant.special_outcome(self).is_some() {
+            return Err(MoveError::GameOver(m));
+        }
+        if !self.is_pseudo_legal(m) {
+            // mismatching pieces are reported first
+            self.check_move(m)?;
+            return Err(MoveError::NotPseudoLegal(m));
+        }
+        self.do_move_inplace(m)?;
+        if !self.variant.is_legal(self, m) {
+            self.undo_move_inplace()?;
+            return Err(MoveError::IllegalMove(m));
+        }
+        Ok(())
+    }
 
     /// Makes a move on the board, like `do_move_inplace()`, but returns _new_ (cloned) board.
-    pub fn do_move(&self, m: Move) -> Result<Board, &'static str> {
+    pub fn do_move(&self, m: Move) -> Result<Board, MoveError> {
         let mut c = self.clone();
-        try!(c.do_move_inplace(m));
+        c.do_move_inplace(m)?;
         Ok(c)
     }
 
     /// Undoes the last move made. Returns `Ok(())` on success and `Err` otherwise
     /// (for example, when it is nothing to undo or the move list is somehow corrupted).
     /// This method _modifies_ the original board.
-    pub fn undo_move_inplace(&mut self) -> Result<(), &'static str> {
-        if let Some(m) = self.move_list.pop() {
+    pub fn undo_move_inplace(&mut self) -> Result<(), MoveError> {
+        if let Some(&m) = self.move_list.last() {
             let from = m.get_square_from();
             let to = m.get_square_to();
-            self.board[to.as_index()] = m.get_captured_piece();
             let p = m.get_moving_piece();
-            if self.board[to.as_index()] != Some(p) {
-                return Err("Square occupied with wrong piece!");
+            let color = p.get_color();
+            let on_target = match m.get_promoted_to() {
+                Some(kind) => Piece::new(kind, color),
+                None => p,
+            };
+            let exploded = self.explodes(m);
+            let geometry = self.get_geometry();
+            if !exploded && self.board[to.as_index()] != Some(on_target) {
+                return Err(MoveError::CorruptedMoveList(m));
             }
-            if let Some(_) = self.board[from.as_index()] {
-                return Err("Starting square somehow occupied");
+            if !m.is_castling() && !m.is_drop() && self.board[from.as_index()].is_some() {
+                return Err(MoveError::CorruptedMoveList(m));
             }
-            self.board[from.as_index()] = if m.get_promoted_to().is_some() {
-                Some(Piece::new(PieceKind::Pawn, p.get_color()))
+            self.move_list.pop();
+            let state = self.history.pop().ok_or(MoveError::CorruptedMoveList(m))?;
+            self.board[to.as_index()] = None;
+            if m.is_castling() {
+                let (rook_from, rook_to) = self.castling_rook_squares(color, is_kingside(m));
+                self.board[rook_to.as_index()] = None;
+                self.board[rook_from.as_index()] = Some(Piece::new(PieceKind::Rook, color));
+            } else if m.is_en_passant() {
+                self.board[to.get_back_in(color, geometry).unwrap().as_index()] =
+                    m.get_captured_piece();
             } else {
-                Some(p)
-            };
+                self.board[to.as_index()] = m.get_captured_piece();
+            }
+            if !m.is_drop() {
+                self.board[from.as_index()] = Some(p);
+            }
+            if exploded {
+                for (&d, p) in NEIGHBOURS.iter().zip(state.exploded.iter()) {
+                    if let Some(p) = *p {
+                        self.board[to.get_by_dir_in(d, geometry).unwrap().as_index()] = Some(p);
+                    }
+                }
+            }
+            if p.get_kind() == PieceKind::King {
+                match color {
+                    Color::White => self.white_king = from,
+                    Color::Black => self.black_king = from,
+                }
+            }
+            self.castling = state.castling;
+            self.en_passant_square = state.en_passant_square;
+            self.halfmove_count = state.halfmove_count;
+            self.hash = state.hash;
+            self.pockets = state.pockets;
+            self.promoted = state.promoted;
+            self.checks_remaining = state.checks_remaining;
+            if color == Color::Black {
+                self.move_number -= 1;
+            }
+            self.side_to_move = color;
             Ok(())
         } else {
-            Err("Nothing to undo")
+            Err(MoveError::NothingToUndo)
         }
     }
 
     /// Undoes the last move made, like `undo_move_inplace()`, but returns _new_ (cloned) board.
-    pub fn undo_move(&self) -> Result<Board, &'static str> {
+    pub fn undo_move(&self) -> Result<Board, MoveError> {
         let mut c = self.clone();
-        try!(c.undo_move_inplace());
+        c.undo_move_inplace()?;
         Ok(c)
     }
+
+    /// Returns `true` if the square `s` is attacked by any piece of color `by`
+    /// (by the variant's rules).
+    pub fn is_square_attacked(&self, s: Square, by: Color) -> bool {
+        self.variant.is_square_attacked(self, s, by)
+    }
+
+    /// Returns the kind of the cheapest piece of color `by` attacking the square `s`.
+    pub fn least_valuable_attacker_kind(&self, s: Square, by: Color) -> Option<PieceKind> {
+        let mut occupied = [false; SQUARE_COUNT];
+        for (o, p) in occupied.iter_mut().zip(&self.board) {
+            *o = p.is_some();
+        }
+        self.least_valuable_attacker(s, by, &occupied).map(|a| a.1)
+    }
+
+    /// Returns `true` if the king of the side to move is in check (by the variant's rules).
+    pub fn is_in_check(&self) -> bool {
+        self.variant.is_in_check(self)
+    }
+
+    /// Returns the outcome if the game is over according to the variant's rules.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.variant.outcome(self)
+    }
+
+    /// Outcome by the usual course: the variant's special rules first, then the lack of
+    /// legal moves (mate or stalemate in standard chess) and draws by rule.
+    pub fn standard_outcome(&self) -> Option<Outcome> {
+        if let Some(outcome) = self.variant.special_outcome(self) {
+            return Some(outcome);
+        }
+        self.standard_outcome_with_moves(&generate_legal_moves(self.clone()))
+    }
+
+    /// Like `standard_outcome()`, for callers that have generated the legal moves already.
+    pub fn standard_outcome_with_moves(&self, legal_moves: &[Move]) -> Option<Outcome> {
+        if let Some(outcome) = self.variant.special_outcome(self) {
+            return Some(outcome);
+        }
+        if legal_moves.is_empty() {
+            return Some(self.variant.no_moves_outcome(self));
+        }
+        if self.halfmove_count >= 100 {
+            Some(Outcome::FiftyMoveRule)
+        } else if self.repetitions() >= 2 {
+            Some(Outcome::ThreefoldRepetition)
+        } else if self.variant.has_insufficient_material(self) {
+            Some(Outcome::InsufficientMaterial)
+        } else {
+            None
+        }
+    }
+
+    /// Only kings, possibly with a single knight or bishop, and no pieces in hand.
+    pub fn has_insufficient_material(&self) -> bool {
+        if self.pockets.iter().flatten().any(|&n| n > 0) {
+            return false;
+        }
+        let mut minors = 0;
+        for p in self.board.iter().filter_map(|p| *p) {
+            match p.get_kind() {
+                PieceKind::King => {}
+                PieceKind::Knight | PieceKind::Bishop => minors += 1,
+                _ => return false,
+            }
+        }
+        minors <= 1
+    }
+
+    /// Returns the move in UCI (long algebraic) notation, like `e2e4`, `e7e8q` or `N@f3`.
+    /// Castling is written as the king's move (`e1g1`) or,
+    /// if `chess960` is `true`, as the king capturing its own rook (`e1h1`).
+    pub fn move_to_uci(&self, m: Move, chess960: bool) -> String {
+        if m.is_drop() {
+            return drop_notation(m);
+        }
+        let from = m.get_square_from();
+        let mut to = m.get_square_to();
+        if m.is_castling() && chess960 {
+            to = self.castling_rook_squares(m.get_moving_piece().get_color(), is_kingside(m)).0;
+        }
+        match m.get_promoted_to() {
+            Some(kind) => format!("{}{}{}", from, to, piece_to_char(Piece::new(kind, Color::Black))),
+            None => format!("{}{}", from, to),
+        }
+    }
+
+    /// Finds a legal move given in UCI notation (see `move_to_uci()`).
+    /// In standard chess castling is accepted in both notations.
+    pub fn parse_uci_move(&self, s: &str, chess960: bool) -> Option<Move> {
+        generate_legal_moves(self.clone()).into_iter().find(|&m| {
+            self.move_to_uci(m, chess960) == s ||
+            (!chess960 && m.is_castling() && self.move_to_uci(m, true) == s)
+        })
+    }
+
+    /// Returns the move in Standard Algebraic Notation, like `Nbd7`, `exd6`, `O-O`, `e8=Q+`
+    /// or `N@f3`.
+    pub fn move_to_san(&self, m: Move) -> String {
+        let p = m.get_moving_piece();
+        let from = m.get_square_from().to_string();
+        let to = m.get_square_to();
+        let mut san = if m.is_drop() {
+            drop_notation(m)
+        } else if m.is_castling() {
+            if is_kingside(m) { "O-O" } else { "O-O-O" }.to_string()
+        } else if p.get_kind() == PieceKind::Pawn {
+            let mut san = String::new();
+            if m.get_captured_piece().is_some() {
+                san.push_str(&from[..1]);
+                san.push('x');
+            }
+            san.push_str(&to.to_string());
+            if let Some(kind) = m.get_promoted_to() {
+                san.push('=');
+                san.push(piece_to_char(Piece::new(kind, Color::White)));
+            }
+            san
+        } else {
+            let mut san = piece_to_char(Piece::new(p.get_kind(), Color::White)).to_string();
+            let others: Vec<Square> = generate_legal_moves(self.clone())
+                .into_iter()
+                .filter(|o| {
+                    o.get_moving_piece() == p && o.get_square_to() == to && o.is_normal_move() &&
+                    o.get_square_from() != m.get_square_from()
+                })
+                .map(|o| o.get_square_from())
+                .collect();
+            if !others.is_empty() {
+                let from_square = m.get_square_from();
+                if others.iter().all(|s| s.get_file() != from_square.get_file()) {
+                    san.push_str(&from[..1]);
+                } else if others.iter().all(|s| s.get_rank() != from_square.get_rank()) {
+                    san.push_str(&from[1..]);
+                } else {
+                    san.push_str(&from);
+                }
+            }
+            if m.get_captured_piece().is_some() {
+                san.push('x');
+            }
+            san.push_str(&to.to_string());
+            san
+        };
+        if let Ok(after) = self.do_move(m) {
+            if after.is_in_check() {
+                san.push(if generate_legal_moves(after).is_empty() { '#' } else { '+' });
+            }
+        }
+        san
+    }
+
+    /// Finds a legal move given in SAN. Check and annotation marks are optional,
+    /// castling may be written with zeros as well and pawn drops without the `P`.
+    pub fn parse_san(&self, s: &str) -> Option<Move> {
+        let normalize = |s: &str| -> String {
+            s.trim_end_matches(|c| "+#!?".contains(c)).replace('0', "O").replace('=', "")
+        };
+        let mut s = normalize(s.trim_end_matches("e.p."));
+        if s.starts_with('@') {
+            s.insert(0, 'P');
+        }
+        generate_legal_moves(self.clone())
+            .into_iter()
+            .find(|&m| normalize(&self.move_to_san(m)) == s)
+    }
+
+    /// Converts a sequence of moves starting from this position to SAN.
+    pub fn line_to_san(&self, line: &[Move]) -> Result<Vec<String>, MoveError> {
+        let mut b = self.clone();
+        let mut res = Vec::new();
+        for &m in line {
+            res.push(b.move_to_san(m));
+            b.do_move_inplace(m)?;
+        }
+        Ok(res)
+    }
+
+    /// Constructs a board from a position in Forsyth-Edwards Notation.
+    /// Castling rights may be given as `KQkq` or, for Chess960, as rook files
+    /// (Shredder-FEN `HAha` or X-FEN, where `K`/`Q` mean the outermost rook).
+    /// The halfmove and fullmove counters may be omitted.
+    pub fn from_fen(fen: &str) -> Result<Board, BoardError> {
+        Board::from_fen_variant(fen, &STANDARD)
+    }
+
+    /// Constructs a board of the given variant from a position in FEN
+    /// (with the variant's extensions).
+    pub fn from_fen_variant(fen: &str,
+                            variant: &'static dyn Variant)
+                            -> Result<Board, BoardError> {
+        let mut b = variant.read_fen(fen)?;
+        b.variant = variant;
+        b.geometry = variant.geometry();
+        b.hash = b.compute_hash();
+        Ok(b)
+    }
+
+    /// Parses standard FEN (see `from_fen()`), for use by variants.
+    pub fn parse_fen(fen: &str) -> Result<Board, BoardError> {
+        Board::parse_fen_checked(fen, true, Geometry::STANDARD, None)
+    }
+
+    /// Parses standard FEN allowing any number of kings, for variants with unusual setups.
+    pub fn parse_fen_relaxed(fen: &str) -> Result<Board, BoardError> {
+        Board::parse_fen_checked(fen, false, Geometry::STANDARD, None)
+    }
+
+    /// Parses FEN like `parse_fen_relaxed()`, with pawns of the given color allowed on their
+    /// first rank as well (White in Horde).
+    pub fn parse_fen_first_rank_pawns(fen: &str, color: Color) -> Result<Board, BoardError> {
+        Board::parse_fen_checked(fen, false, Geometry::STANDARD, Some(color))
+    }
+
+    /// Parses FEN for a board of another size, for use by variants.
+    pub fn parse_fen_geometry(fen: &str, geometry: Geometry) -> Result<Board, BoardError> {
+        Board::parse_fen_checked(fen, true, geometry, None)
+    }
+
+    fn parse_fen_checked(fen: &str,
+                         standard: bool,
+                         geometry: Geometry,
+                         first_rank_pawns: Option<Color>)
+                         -> Result<Board, BoardError> {
+        let fen_error = |text: &'static str, field: &str| BoardError::Fen(text, field.to_string());
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(fen_error("Wrong number of FEN fields", fen));
+        }
+        let files = geometry.get_files() as u32;
+        let mut board = vec![None; SQUARE_COUNT];
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != geometry.get_ranks() as usize {
+            return Err(fen_error("Wrong number of ranks", fields[0]));
+        }
+        for (r, rank) in ranks.iter().enumerate() {
+            let rank_index = Rank::from_int((ranks.len() - 1 - r) as u32);
+            let mut file = 0;
+            let mut chars = rank.chars().peekable();
+            while let Some(c) = chars.next() {
+                if let Some(mut n) = c.to_digit(10) {
+                    // runs of more than nine empty squares on wide boards
+                    while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        n = 10 * n + d;
+                        if n > files {
+                            return Err(fen_error("Wrong number of files", rank));
+                        }
+                        chars.next();
+                    }
+                    file += n;
+                    continue;
+                }
+                if file >= files {
+                    return Err(fen_error("Too many squares in a rank", rank));
+                }
+                let p = piece_from_char(c).ok_or_else(|| fen_error("Wrong piece letter", rank))?;
+                if p.get_kind() == PieceKind::Pawn &&
+                   (rank_index == geometry.back_rank(p.get_color().opposite()) ||
+                    rank_index == geometry.back_rank(p.get_color()) &&
+                    first_rank_pawns != Some(p.get_color())) {
+                    return Err(fen_error("Pawn on the first or the last rank", rank));
+                }
+                board[Square::from_file_and_rank(File::from_int(file), rank_index).as_index()] =
+                    Some(p);
+                file += 1;
+            }
+            if file != files {
+                return Err(fen_error("Wrong number of squares in a rank", rank));
+            }
+        }
+        let side_to_move = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            s => return Err(fen_error("Wrong side to move", s)),
+        };
+        let find_king = |color: Color| {
+            let king = Some(Piece::new(PieceKind::King, color));
+            let mut squares = geometry.squares().filter(|s| board[s.as_index()] == king);
+            match (squares.next(), squares.next()) {
+                (Some(s), None) => Ok(s),
+                (Some(s), Some(_)) if !standard => Ok(s),
+                (None, _) if !standard => {
+                    Ok(Square::from_file_and_rank(File::E, geometry.back_rank(color)))
+                }
+                _ => {
+                    Err(BoardError::InvalidPosition("There must be exactly one king of each \
+                                                     color"))
+                }
+            }
+        };
+        let white_king = find_king(Color::White)?;
+        let black_king = find_king(Color::Black)?;
+        let mut castling = [false; 4];
+        let mut king_file = [None; 2];
+        let mut kings_rook_file = [None; 2];
+        let mut queens_rook_file = [None; 2];
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+                let rank = geometry.back_rank(color);
+                let king = if color == Color::White { white_king } else { black_king };
+                if king.get_rank() != rank ||
+                   board[king.as_index()] != Some(Piece::new(PieceKind::King, color)) {
+                    return Err(BoardError::InvalidPosition("Castling rights without a king on \
+                                                            the back rank"));
+                }
+                let rook = Some(Piece::new(PieceKind::Rook, color));
+                let rook_on = |f: u32| board[Square::from_file_and_rank(File::from_int(f), rank)
+                    .as_index()] == rook;
+                let kf = king.get_file() as u32;
+                let rook_file = match c.to_ascii_lowercase() {
+                    'k' => (kf + 1..files).rev().find(|&f| rook_on(f)),
+                    'q' => (0..kf).find(|&f| rook_on(f)),
+                    f @ 'a'..='j' => {
+                        Some(f as u32 - 'a' as u32).filter(|&f| f < files && rook_on(f))
+                    }
+                    _ => return Err(fen_error("Wrong castling rights", fields[2])),
+                };
+                let rook_file = rook_file
+                    .ok_or(BoardError::InvalidPosition("Castling rights without a rook"))?;
+                if rook_file == kf {
+                    return Err(fen_error("Wrong castling rights", fields[2]));
+                }
+                let kingside = rook_file > kf;
+                let (right, initial_rook_file) = match (color, kingside) {
+                    (Color::White, true) => (CastlingRights::WhiteKingside, &mut kings_rook_file),
+                    (Color::White, false) => (CastlingRights::WhiteQueenside, &mut queens_rook_file),
+                    (Color::Black, true) => (CastlingRights::BlackKingside, &mut kings_rook_file),
+                    (Color::Black, false) => (CastlingRights::BlackQueenside, &mut queens_rook_file),
+                };
+                if castling[right as usize] {
+                    return Err(fen_error("Unsupported castling rights", fields[2]));
+                }
+                castling[right as usize] = true;
+                initial_rook_file[color as usize] = Some(rook_file);
+                king_file[color as usize] = Some(kf);
+            }
+        }
+        let en_passant_square = match fields[3] {
+            "-" => None,
+            s => {
+                let sq = Square::from_str(s)
+                    .ok_or_else(|| fen_error("Wrong en passant square", s))?;
+                let expected = geometry.relative_rank(side_to_move.opposite(), 2);
+                if sq.get_rank() != expected || !geometry.contains(sq) {
+                    return Err(fen_error("Wrong en passant square", s));
+                }
+                Some(sq)
+            }
+        };
+        let halfmove_count = match fields.get(4) {
+            Some(s) => s.parse().map_err(|_| fen_error("Wrong halfmove count", s))?,
+            None => 0,
+        };
+        let move_number = match fields.get(5) {
+            Some(s) => s.parse().map_err(|_| fen_error("Wrong move number", s))?,
+            None => 1,
+        };
+        let king_default = File::from_int(files / 2);
+        let mut b = Board {
+            board,
+            side_to_move,
+            move_number: max(move_number, 1),
+            halfmove_count,
+            en_passant_square,
+            white_king,
+            black_king,
+            castling,
+            initial_king_file: initial_files(king_file, king_default),
+            initial_queens_rook_file: initial_files(queens_rook_file, File::A),
+            initial_kings_rook_file: initial_files(kings_rook_file, geometry.get_last_file()),
+            move_list: Vec::new(),
+            history: Vec::new(),
+            hash: 0,
+            variant: &STANDARD,
+            geometry,
+            pockets: [[0; KIND_COUNT]; 2],
+            promoted: 0,
+            checks_remaining: [3; 2],
+        };
+        b.hash = b.compute_hash();
+        Ok(b)
+    }
+
+    /// Restores a packed move in this position, or returns `None` if it isn't
+    /// a pseudo-legal move here (see `is_pseudo_legal()`).
+    pub fn expand(&self, packed: PackedMove) -> Option<Move> {
+        let from = packed.get_square_from();
+        let to = packed.get_square_to();
+        let m = match packed.get_dropped_kind() {
+            Some(kind) => Move::new_drop(Piece::new(kind, self.side_to_move), to),
+            None => {
+                let captured = if packed.is_en_passant() {
+                    Some(Piece::new(PieceKind::Pawn, self.side_to_move.opposite()))
+                } else if packed.is_castling() {
+                    None
+                } else {
+                    self.get_piece(to)
+                };
+                Move::new(self.get_piece(from)?,
+                          from,
+                          to,
+                          captured,
+                          packed.get_promoted_to(),
+                          packed.is_en_passant(),
+                          packed.is_castling())
+            }
+        };
+        if self.is_pseudo_legal(m) { Some(m) } else { None }
+    }
+
+    /// Returns the position in Forsyth-Edwards Notation. Chess960 castling rights are
+    /// written in X-FEN: `K`/`Q` for the outermost rook, otherwise the rook's file.
+    pub fn to_fen(&self) -> String {
+        self.variant.write_fen(self)
+    }
+
+    /// Writes the piece placement field of FEN, with promoted pieces followed by `~`
+    /// if `mark_promoted` is set (Crazyhouse).
+    pub(crate) fn placement_fen(&self, mark_promoted: bool) -> String {
+        let geometry = self.get_geometry();
+        let mut res = String::new();
+        for r in (0..geometry.get_ranks() as u32).rev() {
+            let mut empty = 0;
+            for f in 0..geometry.get_files() as u32 {
+                let s = Square::from_file_and_rank(File::from_int(f), Rank::from_int(r));
+                match self.get_piece(s) {
+                    Some(p) => {
+                        if empty > 0 {
+                            res.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        res.push(piece_to_char(p));
+                        if mark_promoted && self.is_promoted(s) {
+                            res.push('~');
+                        }
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                res.push_str(&empty.to_string());
+            }
+            if r > 0 {
+                res.push('/');
+            }
+        }
+        res
+    }
+
+    /// Writes standard FEN (see `to_fen()`), for use by variants.
+    pub fn standard_fen(&self) -> String {
+        let geometry = self.get_geometry();
+        let mut res = self.placement_fen(false);
+        res.push_str(if self.side_to_move == Color::White { " w " } else { " b " });
+        let rights = [(CastlingRights::WhiteKingside, Color::White, true),
+                      (CastlingRights::WhiteQueenside, Color::White, false),
+                      (CastlingRights::BlackKingside, Color::Black, true),
+                      (CastlingRights::BlackQueenside, Color::Black, false)];
+        let mut any = false;
+        for &(right, color, kingside) in &rights {
+            if !self.castling[right as usize] {
+                continue;
+            }
+            any = true;
+            let rank = geometry.back_rank(color);
+            let rook_file = if kingside {
+                self.initial_kings_rook_file[color as usize]
+            } else {
+                self.initial_queens_rook_file[color as usize]
+            };
+            let rook = Some(Piece::new(PieceKind::Rook, color));
+            let outer_files: Vec<u32> = if kingside {
+                (rook_file as u32 + 1..geometry.get_files() as u32).collect()
+            } else {
+                (0..rook_file as u32).collect()
+            };
+            let outermost = outer_files.iter().all(|&f| {
+                self.board[Square::from_file_and_rank(File::from_int(f), rank).as_index()] != rook
+            });
+            let c = if outermost {
+                if kingside { 'k' } else { 'q' }
+            } else {
+                format!("{}", rook_file).chars().next().unwrap()
+            };
+            res.push(if color == Color::White { c.to_ascii_uppercase() } else { c });
+        }
+        if !any {
+            res.push('-');
+        }
+        match self.en_passant_square {
+            Some(s) => res.push_str(&format!(" {} ", s)),
+            None => res.push_str(" - "),
+        }
+        res.push_str(&format!("{} {}", self.halfmove_count, self.move_number));
+        res
+    }
+
+    /// Static Exchange Evaluation: the material balance (in centipawns, from the moving side's
+    /// point of view) of the best capture sequence on the target square of `m`,
+    /// with both sides always recapturing with the least valuable piece.
+    /// Sliding pieces hidden behind other attackers (x-rays) join in when uncovered.
+    /// Pins are not taken into account.
+    /// For a quiet move the result is 0 or a negative value if the piece gets lost.
+    pub fn see(&self, m: Move) -> i32 {
+        if m.is_castling() {
+            return 0;
+        }
+        let from = m.get_square_from();
+        let to = m.get_square_to();
+        let geometry = self.get_geometry();
+        let mut occupied = [false; SQUARE_COUNT];
+        for (o, p) in occupied.iter_mut().zip(&self.board) {
+            *o = p.is_some();
+        }
+        occupied[from.as_index()] = false;
+        let mut gain = [0i32; 32];
+        gain[0] = m.get_captured_piece().map_or(0, |p| see_value(p.get_kind()));
+        if m.is_en_passant() {
+            if let Some(s) = to.get_back_in(m.get_moving_piece().get_color(), geometry) {
+                occupied[s.as_index()] = false;
+            }
+        }
+        let mut on_square = m.get_moving_piece().get_kind();
+        if let Some(kind) = m.get_promoted_to() {
+            gain[0] += see_value(kind) - see_value(PieceKind::Pawn);
+            on_square = kind;
+        }
+        let mut side = m.get_moving_piece().get_color().opposite();
+        let mut depth = 0;
+        while let Some((s, kind)) = self.least_valuable_attacker(to, side, &occupied) {
+            if kind == PieceKind::King {
+                // the king may only recapture if the square is no longer defended
+                occupied[s.as_index()] = false;
+                if self.least_valuable_attacker(to, side.opposite(), &occupied).is_some() {
+                    break;
+                }
+            }
+            depth += 1;
+            gain[depth] = see_value(on_square) - gain[depth - 1];
+            if depth == gain.len() - 1 {
+                break;
+            }
+            occupied[s.as_index()] = false;
+            on_square = kind;
+            side = side.opposite();
+        }
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+        gain[0]
+    }
+
+    /// Returns `true` if the Static Exchange Evaluation of `m` is at least `threshold`.
+    pub fn see_ge(&self, m: Move, threshold: i32) -> bool {
+        self.see(m) >= threshold
+    }
+
+    /// Finds the cheapest piece of color `side` attacking `target`,
+    /// considering only pieces on squares marked in `occupied`.
+    fn least_valuable_attacker(&self,
+                               target: Square,
+                               side: Color,
+                               occupied: &[bool; SQUARE_COUNT])
+                               -> Option<(Square, PieceKind)> {
+        let piece_on = |s: Square| if occupied[s.as_index()] {
+            self.board[s.as_index()].filter(|p| p.get_color() == side)
+        } else {
+            None
+        };
+        let geometry = self.get_geometry();
+        let mut best: Option<(Square, PieceKind)> = None;
+        {
+            let mut consider = |s: Square, kind: PieceKind| {
+                if best.is_none_or(|b| see_value(kind) < see_value(b.1)) {
+                    best = Some((s, kind));
+                }
+            };
+            if let Some(b) = target.get_back_in(side, geometry) {
+                let sides = [b.get_by_dir_in(Direction::Left, geometry),
+                             b.get_by_dir_in(Direction::Right, geometry)];
+                for s in sides.iter().flat_map(|s| *s) {
+                    if piece_on(s).map(|p| p.get_kind()) == Some(PieceKind::Pawn) {
+                        return Some((s, PieceKind::Pawn));
+                    }
+                }
+            }
+            for s in target.iter_knight_moves_in(geometry) {
+                match piece_on(s).map(|p| p.get_kind()) {
+                    Some(PieceKind::Knight) => return Some((s, PieceKind::Knight)),
+                    Some(kind @ PieceKind::Archbishop) | Some(kind @ PieceKind::Chancellor) => {
+                        consider(s, kind)
+                    }
+                    _ => {}
+                }
+            }
+            let dirs = [(Direction::UpLeft, PieceKind::Bishop),
+                        (Direction::UpRight, PieceKind::Bishop),
+                        (Direction::DownLeft, PieceKind::Bishop),
+                        (Direction::DownRight, PieceKind::Bishop),
+                        (Direction::Up, PieceKind::Rook),
+                        (Direction::Down, PieceKind::Rook),
+                        (Direction::Left, PieceKind::Rook),
+                        (Direction::Right, PieceKind::Rook)];
+            for &(d, slider) in &dirs {
+                let mut t = target;
+                let mut distance = 0;
+                while let Some(u) = t.get_by_dir_in(d, geometry) {
+                    t = u;
+                    distance += 1;
+                    if !occupied[t.as_index()] {
+                        continue;
+                    }
+                    if let Some(p) = piece_on(t) {
+                        let kind = p.get_kind();
+                        if kind == slider || kind == PieceKind::Queen ||
+                           (kind == PieceKind::Archbishop && slider == PieceKind::Bishop) ||
+                           (kind == PieceKind::Chancellor && slider == PieceKind::Rook) ||
+                           (kind == PieceKind::King && distance == 1) {
+                            consider(t, kind);
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+        best
+    }
+}
+
+pub(crate) fn see_value(kind: PieceKind) -> i32 {
+    match kind {
+        PieceKind::Pawn => 100,
+        PieceKind::Knight => 325,
+        PieceKind::Bishop => 325,
+        PieceKind::Rook => 500,
+        PieceKind::Queen => 1000,
+        PieceKind::King => 20000,
+        PieceKind::Archbishop => 900,
+        PieceKind::Chancellor => 950,
+    }
+}
+
+/// Castling moves go to the C file (queenside) or to the kingside file of
+/// `Board::castling_files()`.
+fn is_kingside(m: Move) -> bool {
+    m.get_square_to().get_file() != File::C
+}
+
+/// Drops are written alike in UCI and SAN: `N@f3`, `P@e4`.
+fn drop_notation(m: Move) -> String {
+    let kind = m.get_moving_piece().get_kind();
+    format!("{}@{}", piece_to_char(Piece::new(kind, Color::White)), m.get_square_to())
+}
+
+/// Hash of the pieces in hand.
+fn pockets_hash(pockets: &[[u8; KIND_COUNT]; 2]) -> u64 {
+    let mut h = 0;
+    for &color in &[Color::White, Color::Black] {
+        for (kind, &count) in ALL_KINDS.iter().zip(pockets[color as usize].iter()) {
+            if count > 0 {
+                h ^= KEYS.pocket(Piece::new(*kind, color), count);
+            }
+        }
+    }
+    h
+}
+
+pub(crate) fn piece_from_char(c: char) -> Option<Piece> {
+    let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+    let kind = match c.to_ascii_lowercase() {
+        'p' => PieceKind::Pawn,
+        'n' => PieceKind::Knight,
+        'b' => PieceKind::Bishop,
+        'r' => PieceKind::Rook,
+        'q' => PieceKind::Queen,
+        'k' => PieceKind::King,
+        'a' => PieceKind::Archbishop,
+        'c' => PieceKind::Chancellor,
+        _ => return None,
+    };
+    Some(Piece::new(kind, color))
+}
+
+/// Files of pieces involved in castling, standard ones for sides without castling rights.
+fn initial_files(files: [Option<u32>; 2], default: File) -> [File; 2] {
+    [files[0].map_or(default, File::from_int), files[1].map_or(default, File::from_int)]
+}
+
+pub(crate) fn piece_to_char(p: Piece) -> char {
+    let c = match p.get_kind() {
+        PieceKind::Pawn => 'p',
+        PieceKind::Knight => 'n',
+        PieceKind::Bishop => 'b',
+        PieceKind::Rook => 'r',
+        PieceKind::Queen => 'q',
+        PieceKind::King => 'k',
+        PieceKind::Archbishop => 'a',
+        PieceKind::Chancellor => 'c',
+    };
+    match p.get_color() {
+        Color::White => c.to_ascii_uppercase(),
+        Color::Black => c,
+    }
 }
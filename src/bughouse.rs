@@ -0,0 +1,260 @@
+use std::time::Duration;
+
+use ::board::*;
+use ::error::*;
+use ::generator::*;
+use ::moves::*;
+use ::pgn::{read_tags, tokenize, wrap_movetext, write_tags, Token};
+use ::piece::*;
+use ::square::*;
+use ::variant::*;
+
+/// One of the two boards of a bughouse game.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BoardId {
+    A,
+    B,
+}
+
+impl BoardId {
+    /// Returns the partner's board.
+    pub fn other(&self) -> BoardId {
+        match *self {
+            BoardId::A => BoardId::B,
+            BoardId::B => BoardId::A,
+        }
+    }
+}
+
+/// Partners play opposite colours on the two boards: teams are named by their colour
+/// on board A.
+pub fn team(board: BoardId, color: Color) -> Color {
+    match board {
+        BoardId::A => color,
+        BoardId::B => color.opposite(),
+    }
+}
+
+/// A move in the combined log of both boards.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TimedMove {
+    pub board: BoardId,
+    pub mv: Move,
+    /// Time since the start of the game.
+    pub time: Duration,
+}
+
+/// The end of a bughouse game, decided by the board that finished first.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BughouseOutcome {
+    pub board: BoardId,
+    pub outcome: Outcome,
+}
+
+impl BughouseOutcome {
+    /// Returns the winning team (see `team()`), or `None` for a draw.
+    pub fn winner(&self) -> Option<Color> {
+        self.outcome.winner().map(|c| team(self.board, c))
+    }
+}
+
+/// A bughouse game: two linked boards, where the pieces captured on one board are
+/// passed to the capturer's partner on the other one.
+#[derive(Clone)]
+pub struct BughouseGame {
+    tags: Vec<(String, String)>,
+    boards: [Board; 2],
+    moves: Vec<TimedMove>,
+}
+
+impl Default for BughouseGame {
+    fn default() -> BughouseGame {
+        BughouseGame::new()
+    }
+}
+
+impl BughouseGame {
+    /// Creates a game from the starting position with the usual BPGN tags.
+    pub fn new() -> BughouseGame {
+        let mut game = BughouseGame {
+            tags: Vec::new(),
+            boards: [Board::new_variant(&BUGHOUSE), Board::new_variant(&BUGHOUSE)],
+            moves: Vec::new(),
+        };
+        for &(name, value) in &[("Event", "?"), ("Site", "?"), ("Date", "????.??.??"),
+                                ("WhiteA", "?"), ("BlackA", "?"), ("WhiteB", "?"),
+                                ("BlackB", "?"), ("Result", "*")] {
+            game.set_tag(name, value);
+        }
+        game
+    }
+
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|t| t.0 == name).map(|t| t.1.as_str())
+    }
+
+    /// Sets a tag, keeping its position if it exists already.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|t| t.0 == name) {
+            Some(t) => t.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn get_tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    pub fn get_board(&self, board: BoardId) -> &Board {
+        &self.boards[board as usize]
+    }
+
+    /// Returns the moves of both boards in the order they were played.
+    pub fn get_moves(&self) -> &[TimedMove] {
+        &self.moves
+    }
+
+    /// Plays a move on one of the boards at the given time since the start,
+    /// passing a captured piece to the partner. The `Result` tag is set when the game ends.
+    pub fn push(&mut self, board: BoardId, m: Move, time: Duration) -> Result<(), MoveError> {
+        if self.outcome().is_some() {
+            return Err(MoveError::GameOver(m));
+        }
+        if self.moves.last().is_some_and(|last| last.time > time) {
+            return Err(MoveError::TimeOrder(m));
+        }
+        let b = &mut self.boards[board as usize];
+        if !generate_legal_moves(b.clone()).contains(&m) {
+            return Err(MoveError::IllegalMove(m));
+        }
+        let captured = m.get_captured_piece().map(|p| {
+            let to = m.get_square_to();
+            let square = if m.is_en_passant() {
+                to.get_back_in(m.get_moving_piece().get_color(), b.get_geometry()).unwrap()
+            } else {
+                to
+            };
+            // promoted pieces are passed on as pawns
+            let kind = if b.is_promoted(square) { PieceKind::Pawn } else { p.get_kind() };
+            (p.get_color(), kind)
+        });
+        b.do_move_inplace(m)?;
+        if let Some((color, kind)) = captured {
+            if self.boards[board.other() as usize].add_to_pocket(color, kind).is_err() {
+                self.boards[board as usize].undo_move_inplace()?;
+                return Err(MoveError::PocketFull(m));
+            }
+        }
+        self.moves.push(TimedMove {
+            board,
+            mv: m,
+            time,
+        });
+        if let Some(outcome) = self.outcome() {
+            let result = match outcome.winner() {
+                Some(Color::White) => "1-0",
+                Some(Color::Black) => "0-1",
+                None => "1/2-1/2",
+            };
+            self.set_tag("Result", result);
+        }
+        Ok(())
+    }
+
+    /// Returns the outcome if the game is over on either board.
+    pub fn outcome(&self) -> Option<BughouseOutcome> {
+        [BoardId::A, BoardId::B].iter().filter_map(|&id| {
+            self.get_board(id).outcome().map(|outcome| BughouseOutcome { board: id, outcome })
+        }).next()
+    }
+
+    /// Writes the game in BPGN. Moves are numbered per board (`12A.` for White and `12a.`
+    /// for Black on board A) and followed by their time in seconds as a comment.
+    pub fn to_bpgn(&self) -> String {
+        let mut res = write_tags(&self.tags);
+        let mut boards = [Board::new_variant(&BUGHOUSE), Board::new_variant(&BUGHOUSE)];
+        let mut tokens = Vec::new();
+        for tm in &self.moves {
+            let b = &mut boards[tm.board as usize];
+            let letter = match (tm.board, b.get_side_to_move()) {
+                (BoardId::A, Color::White) => 'A',
+                (BoardId::A, Color::Black) => 'a',
+                (BoardId::B, Color::White) => 'B',
+                (BoardId::B, Color::Black) => 'b',
+            };
+            tokens.push(format!("{}{}.", b.get_move_number(), letter));
+            tokens.push(b.move_to_san(tm.mv));
+            tokens.push(format!("{{{}.{:03}}}", tm.time.as_secs(), tm.time.subsec_millis()));
+            let captured = tm.mv.get_captured_piece().map(|p| {
+                let square = tm.mv.get_square_to();
+                let promoted = !tm.mv.is_en_passant() && b.is_promoted(square);
+                (p.get_color(), if promoted { PieceKind::Pawn } else { p.get_kind() })
+            });
+            b.do_move_inplace(tm.mv).unwrap();
+            if let Some((color, kind)) = captured {
+                // the moves were checked by `push()`
+                boards[tm.board.other() as usize].add_to_pocket(color, kind).unwrap();
+            }
+        }
+        tokens.push(self.get_tag("Result").unwrap_or("*").to_string());
+        res.push_str(&wrap_movetext(&tokens));
+        res
+    }
+
+    /// Reads a game in BPGN (see `to_bpgn()`). Moves without a time get the time
+    /// of the previous move, other comments are skipped.
+    pub fn from_bpgn(text: &str) -> Result<BughouseGame, BoardError> {
+        let (tags, movetext) = read_tags(text)?;
+        let mut game = BughouseGame::new();
+        game.tags = tags;
+        let mut board = None;
+        let mut pending: Option<(BoardId, Move)> = None;
+        let mut time = Duration::from_secs(0);
+        for token in tokenize(&movetext)? {
+            let token = match token {
+                Token::Word(word) => word,
+                Token::Comment(comment) => {
+                    if let Ok(secs) = comment.trim().parse::<f64>() {
+                        if secs >= 0.0 {
+                            time = Duration::from_millis((secs * 1000.0).round() as u64);
+                        }
+                    }
+                    continue;
+                }
+            };
+            if token.starts_with('$') {
+                continue;
+            }
+            if let Some((id, m)) = pending.take() {
+                game.push(id, m, time)?;
+            }
+            if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token.as_str()) {
+                break;
+            }
+            let san = match token.rfind('.') {
+                Some(dot) => {
+                    let number = token[..dot].trim_end_matches('.');
+                    board = match number.chars().last() {
+                        Some('A') | Some('a') => Some(BoardId::A),
+                        Some('B') | Some('b') => Some(BoardId::B),
+                        _ => return Err(BoardError::Pgn("Wrong move number", token.clone())),
+                    };
+                    &token[dot + 1..]
+                }
+                None => token.as_str(),
+            };
+            if san.is_empty() {
+                continue;
+            }
+            let id = board.ok_or_else(|| BoardError::Pgn("Missing move number", token.clone()))?;
+            let m = game.get_board(id)
+                .parse_san(san)
+                .ok_or_else(|| BoardError::IllegalMove(token.clone()))?;
+            pending = Some((id, m));
+        }
+        if let Some((id, m)) = pending {
+            game.push(id, m, time)?;
+        }
+        Ok(game)
+    }
+}
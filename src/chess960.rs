@@ -0,0 +1,210 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ::error::*;
+use ::piece::*;
+
+/// Returns the back rank (from the a-file to the h-file) of Chess960 starting position
+/// number `position_number` in Scharnagl's numbering (#518 is the standard setup).
+/// Position numbers are treated modulo 960.
+pub fn back_rank(position_number: u32) -> [PieceKind; 8] {
+    let n1 = position_number % 960;
+    let mut piece_order = [PieceKind::Pawn; 8];
+    let mut indices: Vec<usize> = (0..8).collect();
+    let n2 = n1 / 4;
+    let b1 = n1 % 4;
+    let n3 = n2 / 4;
+    let b2 = n2 % 4;
+    let b1idx = (2 * b1 + 1) as usize;
+    let b2idx = (2 * b2) as usize;
+    piece_order[b1idx] = PieceKind::Bishop;
+    piece_order[b2idx] = PieceKind::Bishop;
+    let b1pos = indices.iter().position(|&x| x == b1idx).unwrap();
+    indices.remove(b1pos);
+    let b2pos = indices.iter().position(|&x| x == b2idx).unwrap();
+    indices.remove(b2pos);
+    let n4 = n3 / 6;
+    let q = n3 % 6;
+    let qidx = indices[q as usize];
+    indices.remove(q as usize);
+    piece_order[qidx] = PieceKind::Queen;
+    let kts = KNIGHTS[n4 as usize];
+    piece_order[indices[kts.0]] = PieceKind::Knight;
+    piece_order[indices[kts.1]] = PieceKind::Knight;
+    indices.remove(kts.0);
+    indices.remove(kts.1 - 1); // vector gets shorter, so we must subtract one
+    piece_order[indices[0]] = PieceKind::Rook;
+    piece_order[indices[1]] = PieceKind::King;
+    piece_order[indices[2]] = PieceKind::Rook;
+    piece_order
+}
+
+/// Knight placements among the five squares left after the bishops and the queen.
+const KNIGHTS: [(usize, usize); 10] = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4),
+                                       (2, 3), (2, 4), (3, 4)];
+
+/// Checks that a back rank is a legal Chess960 setup: eight pieces with the usual material,
+/// bishops on squares of opposite colours and the king between the rooks.
+pub fn validate_back_rank(rank: &[PieceKind]) -> Result<(), BoardError> {
+    if rank.len() != 8 {
+        return Err(BoardError::InvalidPosition("Back rank must have eight pieces"));
+    }
+    let count = |kind: PieceKind| rank.iter().filter(|&&k| k == kind).count();
+    if count(PieceKind::King) != 1 || count(PieceKind::Queen) != 1 ||
+       count(PieceKind::Rook) != 2 || count(PieceKind::Bishop) != 2 ||
+       count(PieceKind::Knight) != 2 {
+        return Err(BoardError::InvalidPosition("Wrong pieces on the back rank"));
+    }
+    let bishops: Vec<usize> = (0..8).filter(|&i| rank[i] == PieceKind::Bishop).collect();
+    if bishops[0] % 2 == bishops[1] % 2 {
+        return Err(BoardError::InvalidPosition("Bishops must stand on squares of opposite \
+                                                colours"));
+    }
+    let king = rank.iter().position(|&k| k == PieceKind::King).unwrap();
+    let rooks: Vec<usize> = (0..8).filter(|&i| rank[i] == PieceKind::Rook).collect();
+    if !(rooks[0] < king && king < rooks[1]) {
+        return Err(BoardError::InvalidPosition("King must stand between the rooks"));
+    }
+    Ok(())
+}
+
+/// Returns the Scharnagl number of a back rank, or `None` if it isn't a Chess960 setup.
+pub fn position_number(rank: &[PieceKind]) -> Option<u32> {
+    if validate_back_rank(rank).is_err() {
+        return None;
+    }
+    let light = (0..8).find(|&i| i % 2 == 1 && rank[i] == PieceKind::Bishop).unwrap();
+    let dark = (0..8).find(|&i| i % 2 == 0 && rank[i] == PieceKind::Bishop).unwrap();
+    let rest: Vec<PieceKind> =
+        rank.iter().cloned().filter(|&k| k != PieceKind::Bishop).collect();
+    let q = rest.iter().position(|&k| k == PieceKind::Queen).unwrap();
+    let rest: Vec<PieceKind> = rest.into_iter().filter(|&k| k != PieceKind::Queen).collect();
+    let knights: Vec<usize> = (0..5).filter(|&i| rest[i] == PieceKind::Knight).collect();
+    let n = KNIGHTS.iter().position(|&k| k == (knights[0], knights[1])).unwrap();
+    Some(((n * 6 + q) * 16 + dark / 2 * 4 + light / 2) as u32)
+}
+
+/// Number of Capablanca Random setups: the Chess960 rules for the 10-file back rank,
+/// with the queen and the archbishop on squares of opposite colours as well.
+pub const CAPABLANCA_POSITIONS: u32 = 48000;
+
+/// Returns the back rank (from the a-file to the j-file) of Capablanca Random setup number
+/// `position_number`, treated modulo `CAPABLANCA_POSITIONS`. There is no standard numbering,
+/// this one extends Scharnagl's: light and dark bishop, queen, archbishop among the squares
+/// of the other colour, chancellor, knights, then rook, king and rook on the rest.
+pub fn capablanca_back_rank(position_number: u32) -> [PieceKind; 10] {
+    let mut n = (position_number % CAPABLANCA_POSITIONS) as usize;
+    let mut rank = [PieceKind::Pawn; 10];
+    rank[2 * (n % 5) + 1] = PieceKind::Bishop;
+    n /= 5;
+    rank[2 * (n % 5)] = PieceKind::Bishop;
+    n /= 5;
+    let free = |rank: &[PieceKind; 10]| -> Vec<usize> {
+        (0..10).filter(|&i| rank[i] == PieceKind::Pawn).collect()
+    };
+    let queen = free(&rank)[n % 8];
+    rank[queen] = PieceKind::Queen;
+    n /= 8;
+    let archbishop = free(&rank).into_iter().filter(|&i| i % 2 != queen % 2).nth(n % 4).unwrap();
+    rank[archbishop] = PieceKind::Archbishop;
+    n /= 4;
+    let chancellor = free(&rank)[n % 6];
+    rank[chancellor] = PieceKind::Chancellor;
+    n /= 6;
+    let rest = free(&rank);
+    rank[rest[KNIGHTS[n].0]] = PieceKind::Knight;
+    rank[rest[KNIGHTS[n].1]] = PieceKind::Knight;
+    let rest = free(&rank);
+    rank[rest[0]] = PieceKind::Rook;
+    rank[rest[1]] = PieceKind::King;
+    rank[rest[2]] = PieceKind::Rook;
+    rank
+}
+
+/// Returns the number of a Capablanca Random back rank (see `capablanca_back_rank()`),
+/// or `None` if it isn't a Capablanca Random setup.
+pub fn capablanca_position_number(rank: &[PieceKind]) -> Option<u32> {
+    if rank.len() != 10 {
+        return None;
+    }
+    let count = |kind: PieceKind| rank.iter().filter(|&&k| k == kind).count();
+    let counts = [(PieceKind::King, 1), (PieceKind::Queen, 1), (PieceKind::Archbishop, 1),
+                  (PieceKind::Chancellor, 1), (PieceKind::Rook, 2), (PieceKind::Bishop, 2),
+                  (PieceKind::Knight, 2)];
+    if counts.iter().any(|&(kind, n)| count(kind) != n) {
+        return None;
+    }
+    let find = |kind: PieceKind| rank.iter().position(|&k| k == kind).unwrap();
+    let light = (0..10).find(|&i| i % 2 == 1 && rank[i] == PieceKind::Bishop)?;
+    let dark = (0..10).find(|&i| i % 2 == 0 && rank[i] == PieceKind::Bishop)?;
+    let (queen, archbishop) = (find(PieceKind::Queen), find(PieceKind::Archbishop));
+    let king = find(PieceKind::King);
+    let rooks: Vec<usize> = (0..10).filter(|&i| rank[i] == PieceKind::Rook).collect();
+    if queen % 2 == archbishop % 2 || !(rooks[0] < king && king < rooks[1]) {
+        return None;
+    }
+    // the index of a square among the ones not taken by the kinds placed earlier
+    let index = |square: usize, placed: &[PieceKind]| {
+        (0..square).filter(|&i| !placed.contains(&rank[i])).count()
+    };
+    let q = index(queen, &[PieceKind::Bishop]);
+    let a = (0..archbishop)
+        .filter(|&i| i % 2 != queen % 2 && ![PieceKind::Bishop, PieceKind::Queen].contains(&rank[i]))
+        .count();
+    let c = index(find(PieceKind::Chancellor),
+                  &[PieceKind::Bishop, PieceKind::Queen, PieceKind::Archbishop]);
+    let placed = [PieceKind::Bishop, PieceKind::Queen, PieceKind::Archbishop, PieceKind::Chancellor];
+    let knights: Vec<usize> = (0..10)
+        .filter(|&i| rank[i] == PieceKind::Knight)
+        .map(|i| index(i, &placed))
+        .collect();
+    let k = KNIGHTS.iter().position(|&k| k == (knights[0], knights[1]))?;
+    Some((light / 2 + 5 * (dark / 2 + 5 * (q + 8 * (a + 4 * (c + 6 * k))))) as u32)
+}
+
+/// Seedable pseudo-random generator (xorshift64*) for reproducible random setups.
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        // zero is a fixed point of xorshift
+        Random { state: if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed } }
+    }
+
+    /// Generator seeded from the system time.
+    pub fn from_time() -> Random {
+        let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        Random::new(t.as_secs() ^ (t.subsec_nanos() as u64) << 32)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a uniformly distributed number below `n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        // rejection sampling avoids the modulo bias
+        let limit = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < limit {
+                return x % n;
+            }
+        }
+    }
+
+    /// Returns a uniformly distributed Chess960 position number.
+    pub fn position_number(&mut self) -> u32 {
+        self.below(960) as u32
+    }
+
+    /// Returns a uniformly distributed Capablanca Random position number.
+    pub fn capablanca_position_number(&mut self) -> u32 {
+        self.below(CAPABLANCA_POSITIONS as u64) as u32
+    }
+}
@@ -0,0 +1,154 @@
+use std::error::Error;
+use std::fmt;
+
+use ::moves::*;
+use ::square::*;
+
+/// Errors of making and unmaking moves, with the offending move.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MoveError {
+    /// The starting square is empty.
+    EmptySquare(Move, Square),
+    /// Another piece stands on the starting square.
+    WrongMovingPiece(Move, Square),
+    /// The captured piece doesn't stand on the given square.
+    WrongCapturedPiece(Move, Square),
+    /// The king or the rook isn't in place for castling.
+    WrongCastling(Move),
+    /// A piece is dropped on an occupied square.
+    OccupiedSquare(Move, Square),
+    /// The dropped piece is not in the pocket.
+    NotInPocket(Move),
+    /// The move doesn't follow the rules of the moving piece.
+    NotPseudoLegal(Move),
+    /// The move is not legal in the position (it leaves the king in check, for example).
+    IllegalMove(Move),
+    /// The game is already over.
+    GameOver(Move),
+    /// Moves of a bughouse game are added out of time order.
+    TimeOrder(Move),
+    /// The partner's pocket can't take the captured piece (bughouse).
+    PocketFull(Move),
+    /// The board doesn't match the last move when undoing it.
+    CorruptedMoveList(Move),
+    NothingToUndo,
+}
+
+/// Writes a move with its squares only, like `e2e4` or `@e4` for a drop.
+fn write_move(f: &mut fmt::Formatter, m: &Move) -> fmt::Result {
+    if m.is_drop() {
+        write!(f, "@{}", m.get_square_to())
+    } else {
+        write!(f, "{}{}", m.get_square_from(), m.get_square_to())
+    }
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (text, m, s) = match *self {
+            MoveError::EmptySquare(m, s) => ("Empty starting square", m, Some(s)),
+            MoveError::WrongMovingPiece(m, s) => ("Wrong moving piece", m, Some(s)),
+            MoveError::WrongCapturedPiece(m, s) => ("Wrong captured piece", m, Some(s)),
+            MoveError::WrongCastling(m) => ("Wrong castling", m, None),
+            MoveError::OccupiedSquare(m, s) => ("Drop on an occupied square", m, Some(s)),
+            MoveError::NotInPocket(m) => ("Dropped piece is not in the pocket", m, None),
+            MoveError::NotPseudoLegal(m) => ("Move against the rules of the piece", m, None),
+            MoveError::IllegalMove(m) => ("Illegal move", m, None),
+            MoveError::GameOver(m) => ("Game is over", m, None),
+            MoveError::TimeOrder(m) => ("Moves must be in time order", m, None),
+            MoveError::PocketFull(m) => ("Pocket is full", m, None),
+            MoveError::CorruptedMoveList(m) => ("Move list is corrupted", m, None),
+            MoveError::NothingToUndo => return write!(f, "Nothing to undo"),
+        };
+        write!(f, "{}", text)?;
+        if let Some(s) = s {
+            write!(f, " on {}", s)?;
+        }
+        write!(f, " (")?;
+        write_move(f, &m)?;
+        write!(f, ")")
+    }
+}
+
+impl Error for MoveError {}
+
+/// Errors of reading positions, moves and games (FEN, SAN, PGN and the like),
+/// with the offending text.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BoardError {
+    /// Malformed FEN: what is wrong and the field in question.
+    Fen(&'static str, String),
+    /// A position (or a setup) not allowed by the rules: what is wrong.
+    InvalidPosition(&'static str),
+    /// A move that can't be parsed or isn't legal, as written.
+    IllegalMove(String),
+    /// Malformed PGN: what is wrong and the text in question.
+    Pgn(&'static str, String),
+    /// Malformed Betza notation: what is wrong and the notation.
+    Betza(&'static str, String),
+    UnsupportedVariant(String),
+    /// A move couldn't be made or undone.
+    Move(MoveError),
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BoardError::Fen(text, ref field) => write!(f, "{} in FEN: {}", text, field),
+            BoardError::InvalidPosition(text) => write!(f, "{}", text),
+            BoardError::IllegalMove(ref m) => write!(f, "Illegal move {}", m),
+            BoardError::Pgn(text, ref part) => write!(f, "{} in PGN: {}", text, part),
+            BoardError::Betza(text, ref notation) => write!(f, "{} in Betza: {}", text, notation),
+            BoardError::UnsupportedVariant(ref name) => write!(f, "Unsupported variant {}", name),
+            BoardError::Move(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for BoardError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            BoardError::Move(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<MoveError> for BoardError {
+    fn from(e: MoveError) -> BoardError {
+        BoardError::Move(e)
+    }
+}
+
+/// Errors of reading evaluation weights, with the line in question.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParamsError {
+    /// The weights file couldn't be read: the I/O error message.
+    Io(String),
+    /// A line before any entry isn't `name = values`.
+    Syntax(usize),
+    /// A value isn't an integer: the line and the value.
+    Value(usize, String),
+    /// No weight has the name: the line and the name.
+    UnknownWeight(usize, String),
+    /// A wrong number of values: the line, the weight, the expected and the given counts.
+    WrongCount(usize, String, usize, usize),
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParamsError::Io(ref e) => write!(f, "Can't read weights: {}", e),
+            ParamsError::Syntax(n) => write!(f, "line {}: expected `name = values`", n),
+            ParamsError::Value(n, ref v) => write!(f, "line {}: invalid value `{}`", n, v),
+            ParamsError::UnknownWeight(n, ref name) => {
+                write!(f, "line {}: unknown weight `{}`", n, name)
+            }
+            ParamsError::WrongCount(n, ref name, expected, given) => {
+                write!(f, "line {}: `{}` expects {} values, got {}", n, name, expected, given)
+            }
+        }
+    }
+}
+
+impl Error for ParamsError {}
@@ -0,0 +1,576 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use ::board::*;
+use ::error::*;
+use ::piece::*;
+use ::square::*;
+
+/// A pair of middlegame and endgame values.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Score {
+    pub fn new(mg: i32, eg: i32) -> Score {
+        Score { mg, eg }
+    }
+
+    /// Interpolates between middlegame and endgame values.
+    /// `phase` goes from `max_phase` (all pieces on the board) down to 0 (bare kings and pawns).
+    pub fn taper(&self, phase: i32, max_phase: i32) -> i32 {
+        if max_phase <= 0 {
+            return self.eg;
+        }
+        (self.mg * phase + self.eg * (max_phase - phase)) / max_phase
+    }
+
+    fn add(&mut self, mg: i32, eg: i32) {
+        self.mg += mg;
+        self.eg += eg;
+    }
+}
+
+/// Per-term evaluation result, useful for debugging and tuning.
+/// All terms are given from White's point of view, only `total` is relative to the side to move.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct EvalBreakdown {
+    pub material: Score,
+    pub psqt: Score,
+    pub mobility: Score,
+    pub king_safety: Score,
+    pub pawn_structure: Score,
+    /// Game phase, from `max_phase` (opening) to 0 (endgame).
+    pub phase: i32,
+    pub max_phase: i32,
+    /// Final tapered score in centipawns from the side to move's point of view.
+    pub total: i32,
+}
+
+impl EvalBreakdown {
+    /// Sum of all the terms (from White's point of view).
+    pub fn sum(&self) -> Score {
+        let mut s = Score::default();
+        for t in &[self.material, self.psqt, self.mobility, self.king_safety, self.pawn_structure] {
+            s.add(t.mg, t.eg);
+        }
+        s
+    }
+}
+
+impl fmt::Display for EvalBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let terms = [("material", self.material),
+                     ("psqt", self.psqt),
+                     ("mobility", self.mobility),
+                     ("king safety", self.king_safety),
+                     ("pawn structure", self.pawn_structure),
+                     ("sum", self.sum())];
+        writeln!(f, "{:>16} {:>6} {:>6} {:>6}", "term", "mg", "eg", "total")?;
+        for &(name, s) in &terms {
+            writeln!(f,
+                     "{:>16} {:>6} {:>6} {:>6}",
+                     name,
+                     s.mg,
+                     s.eg,
+                     s.taper(self.phase, self.max_phase))?;
+        }
+        write!(f, "phase {}/{}, side to move: {}", self.phase, self.max_phase, self.total)
+    }
+}
+
+/// Evaluation weights. Piece-indexed arrays are in `PieceKind` order (pawn to king),
+/// piece-square tables are indexed from White's point of view, a8 first and h1 last.
+/// Compound pieces (archbishop and chancellor) are evaluated as the sum of their parts,
+/// other board sizes are mapped onto the 8x8 tables.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvalParams {
+    pub material_mg: [i32; 6],
+    pub material_eg: [i32; 6],
+    pub psqt_mg: [[i32; 64]; 6],
+    pub psqt_eg: [[i32; 64]; 6],
+    pub phase_weights: [i32; 6],
+    pub mobility_mg: [i32; 6],
+    pub mobility_eg: [i32; 6],
+    pub king_attack_mg: [i32; 6],
+    pub king_attack_eg: [i32; 6],
+    pub pawn_shield: [i32; 2],
+    pub doubled_pawn: [i32; 2],
+    pub isolated_pawn: [i32; 2],
+    pub passed_pawn_mg: [i32; 8],
+    pub passed_pawn_eg: [i32; 8],
+}
+
+const PIECE_NAMES: [&str; 6] = ["pawn", "knight", "bishop", "rook", "queen", "king"];
+
+impl Default for EvalParams {
+    fn default() -> EvalParams {
+        EvalParams {
+            material_mg: [82, 337, 365, 477, 1025, 0],
+            material_eg: [94, 281, 297, 512, 936, 0],
+            psqt_mg: [PAWN_MG, KNIGHT_MG, BISHOP_MG, ROOK_MG, QUEEN_MG, KING_MG],
+            psqt_eg: [PAWN_EG, KNIGHT_EG, BISHOP_EG, ROOK_EG, QUEEN_EG, KING_EG],
+            phase_weights: [0, 1, 1, 2, 4, 0],
+            mobility_mg: [0, 4, 5, 2, 1, 0],
+            mobility_eg: [0, 4, 5, 4, 2, 0],
+            king_attack_mg: [0, 7, 5, 8, 12, 0],
+            king_attack_eg: [0, 0, 0, 0, 0, 0],
+            pawn_shield: [12, 0],
+            doubled_pawn: [-10, -20],
+            isolated_pawn: [-12, -10],
+            passed_pawn_mg: [0, 0, 5, 10, 20, 35, 60, 0],
+            passed_pawn_eg: [0, 10, 15, 25, 45, 75, 120, 0],
+        }
+    }
+}
+
+impl EvalParams {
+    /// Maximum game phase value, reached with all the pieces of the initial position on the board.
+    pub fn max_phase(&self) -> i32 {
+        2 * (2 * self.phase_weights[1] + 2 * self.phase_weights[2] + 2 * self.phase_weights[3] +
+             self.phase_weights[4])
+    }
+
+    /// Reads weights from a file (see `parse()` for the format).
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<EvalParams, ParamsError> {
+        let text = fs::read_to_string(path).map_err(|e| ParamsError::Io(e.to_string()))?;
+        EvalParams::parse(&text)
+    }
+
+    /// Parses weights from text. Each entry is `name = value value ...`, lines without `=`
+    /// continue the previous entry and `#` starts a comment. Weights not mentioned keep their
+    /// default values, so a file may override only the terms being tuned.
+    /// The output of `to_string()` is accepted as well.
+    pub fn parse(text: &str) -> Result<EvalParams, ParamsError> {
+        let mut entries: Vec<(usize, &str, String)> = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let first = parts.next().unwrap().trim();
+            match parts.next() {
+                Some(values) => entries.push((n + 1, first, values.to_string())),
+                None => {
+                    match entries.last_mut() {
+                        Some(e) => {
+                            e.2.push(' ');
+                            e.2.push_str(first);
+                        }
+                        None => return Err(ParamsError::Syntax(n + 1)),
+                    }
+                }
+            }
+        }
+        let mut params = EvalParams::default();
+        for (n, name, values) in entries {
+            let values = values.split_whitespace()
+                .map(|v| v.parse::<i32>().map_err(|_| ParamsError::Value(n, v.to_string())))
+                .collect::<Result<Vec<i32>, _>>()?;
+            let target = match params.field_mut(name) {
+                Some(t) => t,
+                None => return Err(ParamsError::UnknownWeight(n, name.to_string())),
+            };
+            if target.len() != values.len() {
+                return Err(ParamsError::WrongCount(n,
+                                                   name.to_string(),
+                                                   target.len(),
+                                                   values.len()));
+            }
+            target.copy_from_slice(&values);
+        }
+        Ok(params)
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut [i32]> {
+        if let Some(rest) = name.strip_prefix("psqt_mg_") {
+            let i = PIECE_NAMES.iter().position(|&p| p == rest)?;
+            return Some(&mut self.psqt_mg[i]);
+        }
+        if let Some(rest) = name.strip_prefix("psqt_eg_") {
+            let i = PIECE_NAMES.iter().position(|&p| p == rest)?;
+            return Some(&mut self.psqt_eg[i]);
+        }
+        Some(match name {
+            "material_mg" => &mut self.material_mg,
+            "material_eg" => &mut self.material_eg,
+            "phase_weights" => &mut self.phase_weights,
+            "mobility_mg" => &mut self.mobility_mg,
+            "mobility_eg" => &mut self.mobility_eg,
+            "king_attack_mg" => &mut self.king_attack_mg,
+            "king_attack_eg" => &mut self.king_attack_eg,
+            "pawn_shield" => &mut self.pawn_shield,
+            "doubled_pawn" => &mut self.doubled_pawn,
+            "isolated_pawn" => &mut self.isolated_pawn,
+            "passed_pawn_mg" => &mut self.passed_pawn_mg,
+            "passed_pawn_eg" => &mut self.passed_pawn_eg,
+            _ => return None,
+        })
+    }
+}
+
+fn write_values(f: &mut fmt::Formatter, name: &str, values: &[i32]) -> fmt::Result {
+    write!(f, "{} =", name)?;
+    for (i, v) in values.iter().enumerate() {
+        if values.len() == 64 && i % 8 == 0 {
+            write!(f, "\n   ")?;
+        }
+        write!(f, " {}", v)?;
+    }
+    writeln!(f)
+}
+
+impl fmt::Display for EvalParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_values(f, "material_mg", &self.material_mg)?;
+        write_values(f, "material_eg", &self.material_eg)?;
+        write_values(f, "phase_weights", &self.phase_weights)?;
+        write_values(f, "mobility_mg", &self.mobility_mg)?;
+        write_values(f, "mobility_eg", &self.mobility_eg)?;
+        write_values(f, "king_attack_mg", &self.king_attack_mg)?;
+        write_values(f, "king_attack_eg", &self.king_attack_eg)?;
+        write_values(f, "pawn_shield", &self.pawn_shield)?;
+        write_values(f, "doubled_pawn", &self.doubled_pawn)?;
+        write_values(f, "isolated_pawn", &self.isolated_pawn)?;
+        write_values(f, "passed_pawn_mg", &self.passed_pawn_mg)?;
+        write_values(f, "passed_pawn_eg", &self.passed_pawn_eg)?;
+        for (i, name) in PIECE_NAMES.iter().enumerate() {
+            write_values(f, &format!("psqt_mg_{}", name), &self.psqt_mg[i])?;
+            write_values(f, &format!("psqt_eg_{}", name), &self.psqt_eg[i])?;
+        }
+        Ok(())
+    }
+}
+
+/// Evaluates the position in centipawns from the side to move's point of view.
+pub fn evaluate(board: &Board, params: &EvalParams) -> i32 {
+    evaluate_breakdown(board, params).total
+}
+
+/// Evaluates the position like `evaluate()`, but also returns all the terms separately.
+pub fn evaluate_breakdown(board: &Board, params: &EvalParams) -> EvalBreakdown {
+    let mut res = EvalBreakdown::default();
+    let geometry = board.get_geometry();
+    let (files, ranks) = (geometry.get_files() as usize, geometry.get_ranks() as usize);
+    let mut pawn_files = [[0u8; MAX_FILES]; 2];
+    let mut pawn_ranks: [Vec<(usize, usize)>; 2] = [Vec::new(), Vec::new()];
+    let mut king_zones = [[false; SQUARE_COUNT]; 2];
+    // Horde and Antichess may have no king (or a stale king square)
+    for &color in [Color::White, Color::Black].iter().filter(|&&c| board.has_king(c)) {
+        let k = board.get_king_square(color);
+        king_zones[color as usize][k.as_index()] = true;
+        for d in &QUEEN_DIRS {
+            if let Some(t) = k.get_by_dir_in(*d, geometry) {
+                king_zones[color as usize][t.as_index()] = true;
+            }
+        }
+    }
+    for (s, p) in geometry.squares().filter_map(|s| board.get_piece(s).map(|p| (s, p))) {
+        let color = p.get_color();
+        let sign = if color == Color::White { 1 } else { -1 };
+        // the square scaled to 8x8, then seen from White's side with a8 first
+        let f = s.get_file() as usize * 8 / files;
+        let r = match color {
+            Color::White => s.get_rank() as usize,
+            Color::Black => ranks - 1 - s.get_rank() as usize,
+        } * 8 / ranks;
+        let table_index = (7 - r) * 8 + f;
+        for &part in components(p.get_kind()) {
+            let kind = part as usize;
+            res.phase += params.phase_weights[kind];
+            res.material.add(sign * params.material_mg[kind], sign * params.material_eg[kind]);
+            res.psqt.add(sign * params.psqt_mg[kind][table_index],
+                         sign * params.psqt_eg[kind][table_index]);
+            match part {
+                PieceKind::Pawn => {
+                    pawn_files[color as usize][s.get_file() as usize] += 1;
+                    pawn_ranks[color as usize]
+                        .push((s.get_file() as usize, s.get_rank() as usize));
+                }
+                PieceKind::King => {}
+                _ => {
+                    let mut mobility = 0;
+                    let mut zone_attacks = 0;
+                    let enemy_zone = &king_zones[1 - color as usize];
+                    for t in attacked_squares(board, s, part) {
+                        match board.get_piece(t) {
+                            Some(q) if q.get_color() == color => {}
+                            _ => mobility += 1,
+                        }
+                        if enemy_zone[t.as_index()] {
+                            zone_attacks += 1;
+                        }
+                    }
+                    res.mobility.add(sign * mobility * params.mobility_mg[kind],
+                                     sign * mobility * params.mobility_eg[kind]);
+                    res.king_safety.add(sign * zone_attacks * params.king_attack_mg[kind],
+                                        sign * zone_attacks * params.king_attack_eg[kind]);
+                }
+            }
+        }
+    }
+    for &color in &[Color::White, Color::Black] {
+        let c = color as usize;
+        let sign = if color == Color::White { 1 } else { -1 };
+        // pieces in hand (Crazyhouse)
+        for &kind in &[PieceKind::Pawn, PieceKind::Knight, PieceKind::Bishop, PieceKind::Rook,
+                       PieceKind::Queen] {
+            let count = board.get_pocket(color, kind) as i32;
+            res.material.add(sign * count * params.material_mg[kind as usize],
+                             sign * count * params.material_eg[kind as usize]);
+        }
+        // pawn shield: own pawns on the king's and adjacent files, one or two ranks ahead
+        let mut shield = 0;
+        if board.has_king(color) {
+            let k = board.get_king_square(color);
+            let (kf, kr) = (k.get_file() as i32, k.get_rank() as i32);
+            let forward = if color == Color::White { 1 } else { -1 };
+            for &(f, r) in &pawn_ranks[c] {
+                let (f, r) = (f as i32, r as i32);
+                if (f - kf).abs() <= 1 && (r - kr == forward || r - kr == 2 * forward) {
+                    shield += 1;
+                }
+            }
+        }
+        res.king_safety.add(sign * shield * params.pawn_shield[0],
+                            sign * shield * params.pawn_shield[1]);
+        for f in 0..files {
+            let count = pawn_files[c][f] as i32;
+            if count > 1 {
+                res.pawn_structure.add(sign * (count - 1) * params.doubled_pawn[0],
+                                       sign * (count - 1) * params.doubled_pawn[1]);
+            }
+            let left = if f > 0 { pawn_files[c][f - 1] } else { 0 };
+            let right = if f + 1 < files { pawn_files[c][f + 1] } else { 0 };
+            if count > 0 && left == 0 && right == 0 {
+                res.pawn_structure.add(sign * count * params.isolated_pawn[0],
+                                       sign * count * params.isolated_pawn[1]);
+            }
+        }
+        for &(f, r) in &pawn_ranks[c] {
+            let passed = pawn_ranks[1 - c].iter().all(|&(ef, er)| {
+                (ef as i32 - f as i32).abs() > 1 ||
+                match color {
+                    Color::White => er <= r,
+                    Color::Black => er >= r,
+                }
+            });
+            if passed {
+                // counted back from the promotion rank, so that index 6 is always the last step
+                let to_go = if color == Color::White { ranks - 1 - r } else { r };
+                let relative_rank = 7usize.saturating_sub(to_go);
+                res.pawn_structure.add(sign * params.passed_pawn_mg[relative_rank],
+                                       sign * params.passed_pawn_eg[relative_rank]);
+            }
+        }
+    }
+    res.max_phase = params.max_phase();
+    res.phase = res.phase.min(res.max_phase);
+    let total = res.sum().taper(res.phase, res.max_phase);
+    res.total = match board.get_side_to_move() {
+        Color::White => total,
+        Color::Black => -total,
+    };
+    res
+}
+
+const ROOK_DIRS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+const BISHOP_DIRS: [Direction; 4] = [Direction::UpLeft,
+                                     Direction::UpRight,
+                                     Direction::DownLeft,
+                                     Direction::DownRight];
+const QUEEN_DIRS: [Direction; 8] = [Direction::Up,
+                                    Direction::Down,
+                                    Direction::Left,
+                                    Direction::Right,
+                                    Direction::UpLeft,
+                                    Direction::UpRight,
+                                    Direction::DownLeft,
+                                    Direction::DownRight];
+
+/// The standard pieces a piece is made of (the piece itself if it is one of them).
+fn components(kind: PieceKind) -> &'static [PieceKind] {
+    match kind {
+        PieceKind::Pawn => &[PieceKind::Pawn],
+        PieceKind::Knight => &[PieceKind::Knight],
+        PieceKind::Bishop => &[PieceKind::Bishop],
+        PieceKind::Rook => &[PieceKind::Rook],
+        PieceKind::Queen => &[PieceKind::Queen],
+        PieceKind::King => &[PieceKind::King],
+        PieceKind::Archbishop => &[PieceKind::Bishop, PieceKind::Knight],
+        PieceKind::Chancellor => &[PieceKind::Rook, PieceKind::Knight],
+    }
+}
+
+/// Squares attacked by a knight or a slider standing on `s` (including squares with own pieces).
+fn attacked_squares(board: &Board, s: Square, kind: PieceKind) -> Vec<Square> {
+    let geometry = board.get_geometry();
+    let dirs: &[Direction] = match kind {
+        PieceKind::Knight => return s.get_knight_moves_in(geometry),
+        PieceKind::Bishop => &BISHOP_DIRS,
+        PieceKind::Rook => &ROOK_DIRS,
+        _ => &QUEEN_DIRS,
+    };
+    let mut res = Vec::new();
+    for d in dirs {
+        let mut t = s;
+        while let Some(u) = t.get_by_dir_in(*d, geometry) {
+            t = u;
+            res.push(t);
+            if board.get_piece(t).is_some() {
+                break;
+            }
+        }
+    }
+    res
+}
+
+// Piece-square tables from PeSTO (Ronald Friederich), a8 first, from White's point of view.
+
+#[rustfmt::skip]
+const PAWN_MG: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     98, 134,  61,  95,  68, 126,  34, -11,
+     -6,   7,  26,  31,  65,  56,  25, -20,
+    -14,  13,   6,  21,  23,  12,  17, -23,
+    -27,  -2,  -5,  12,  17,   6,  10, -25,
+    -26,  -4,  -4, -10,   3,   3,  33, -12,
+    -35,  -1, -20, -23, -15,  24,  38, -22,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const PAWN_EG: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+    178, 173, 158, 134, 147, 132, 165, 187,
+     94, 100,  85,  67,  56,  53,  82,  84,
+     32,  24,  13,   5,  -2,   4,  17,  17,
+     13,   9,  -3,  -7,  -7,  -8,   3,  -1,
+      4,   7,  -6,   1,   0,  -5,  -1,  -8,
+     13,   8,   8,  10,  13,   0,   2,  -7,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_MG: [i32; 64] = [
+    -167, -89, -34, -49,  61, -97, -15, -107,
+     -73, -41,  72,  36,  23,  62,   7,  -17,
+     -47,  60,  37,  65,  84, 129,  73,   44,
+      -9,  17,  19,  53,  37,  69,  18,   22,
+     -13,   4,  16,  13,  28,  19,  21,   -8,
+     -23,  -9,  12,  10,  19,  17,  25,  -16,
+     -29, -53, -12,  -3,  -1,  18, -14,  -19,
+    -105, -21, -58, -33, -17, -28, -19,  -23,
+];
+
+#[rustfmt::skip]
+const KNIGHT_EG: [i32; 64] = [
+    -58, -38, -13, -28, -31, -27, -63, -99,
+    -25,  -8, -25,  -2,  -9, -25, -24, -52,
+    -24, -20,  10,   9,  -1,  -9, -19, -41,
+    -17,   3,  22,  22,  22,  11,   8, -18,
+    -18,  -6,  16,  25,  16,  17,   4, -18,
+    -23,  -3,  -1,  15,  10,  -3, -20, -22,
+    -42, -20, -10,  -5,  -2, -20, -23, -44,
+    -29, -51, -23, -15, -22, -18, -50, -64,
+];
+
+#[rustfmt::skip]
+const BISHOP_MG: [i32; 64] = [
+    -29,   4, -82, -37, -25, -42,   7,  -8,
+    -26,  16, -18, -13,  30,  59,  18, -47,
+    -16,  37,  43,  40,  35,  50,  37,  -2,
+     -4,   5,  19,  50,  37,  37,   7,  -2,
+     -6,  13,  13,  26,  34,  12,  10,   4,
+      0,  15,  15,  15,  14,  27,  18,  10,
+      4,  15,  16,   0,   7,  21,  33,   1,
+    -33,  -3, -14, -21, -13, -12, -39, -21,
+];
+
+#[rustfmt::skip]
+const BISHOP_EG: [i32; 64] = [
+    -14, -21, -11,  -8,  -7,  -9, -17, -24,
+     -8,  -4,   7, -12,  -3, -13,  -4, -14,
+      2,  -8,   0,  -1,  -2,   6,   0,   4,
+     -3,   9,  12,   9,  14,  10,   3,   2,
+     -6,   3,  13,  19,   7,  10,  -3,  -9,
+    -12,  -3,   8,  10,  13,   3,  -7, -15,
+    -14, -18,  -7,  -1,   4,  -9, -15, -27,
+    -23,  -9, -23,  -5,  -9, -16,  -5, -17,
+];
+
+#[rustfmt::skip]
+const ROOK_MG: [i32; 64] = [
+     32,  42,  32,  51,  63,   9,  31,  43,
+     27,  32,  58,  62,  80,  67,  26,  44,
+     -5,  19,  26,  36,  17,  45,  61,  16,
+    -24, -11,   7,  26,  24,  35,  -8, -20,
+    -36, -26, -12,  -1,   9,  -7,   6, -23,
+    -45, -25, -16, -17,   3,   0,  -5, -33,
+    -44, -16, -20,  -9,  -1,  11,  -6, -71,
+    -19, -13,   1,  17,  16,   7, -37, -26,
+];
+
+#[rustfmt::skip]
+const ROOK_EG: [i32; 64] = [
+     13,  10,  18,  15,  12,  12,   8,   5,
+     11,  13,  13,  11,  -3,   3,   8,   3,
+      7,   7,   7,   5,   4,  -3,  -5,  -3,
+      4,   3,  13,   1,   2,   1,  -1,   2,
+      3,   5,   8,   4,  -5,  -6,  -8, -11,
+     -4,   0,  -5,  -1,  -7, -12,  -8, -16,
+     -6,  -6,   0,   2,  -9,  -9, -11,  -3,
+     -9,   2,   3,  -1,  -5, -13,   4, -20,
+];
+
+#[rustfmt::skip]
+const QUEEN_MG: [i32; 64] = [
+    -28,   0,  29,  12,  59,  44,  43,  45,
+    -24, -39,  -5,   1, -16,  57,  28,  54,
+    -13, -17,   7,   8,  29,  56,  47,  57,
+    -27, -27, -16, -16,  -1,  17,  -2,   1,
+     -9, -26,  -9, -10,  -2,  -4,   3,  -3,
+    -14,   2, -11,  -2,  -5,   2,  14,   5,
+    -35,  -8,  11,   2,   8,  15,  -3,   1,
+     -1, -18,  -9,  10, -15, -25, -31, -50,
+];
+
+#[rustfmt::skip]
+const QUEEN_EG: [i32; 64] = [
+     -9,  22,  22,  27,  27,  19,  10,  20,
+    -17,  20,  32,  41,  58,  25,  30,   0,
+    -20,   6,   9,  49,  47,  35,  19,   9,
+      3,  22,  24,  45,  57,  40,  57,  36,
+    -18,  28,  19,  47,  31,  34,  39,  23,
+    -16, -27,  15,   6,   9,  17,  10,   5,
+    -22, -23, -30, -16, -16, -23, -36, -32,
+    -33, -28, -22, -43,  -5, -32, -20, -41,
+];
+
+#[rustfmt::skip]
+const KING_MG: [i32; 64] = [
+    -65,  23,  16, -15, -56, -34,   2,  13,
+     29,  -1, -20,  -7,  -8,  -4, -38, -29,
+     -9,  24,   2, -16, -20,   6,  22, -22,
+    -17, -20, -12, -27, -30, -25, -14, -36,
+    -49,  -1, -27, -39, -46, -44, -33, -51,
+    -14, -14, -22, -46, -44, -30, -15, -27,
+      1,   7,  -8, -64, -43, -16,   9,   8,
+    -15,  36,  12, -54,   8, -28,  24,  14,
+];
+
+#[rustfmt::skip]
+const KING_EG: [i32; 64] = [
+    -74, -35, -18, -18, -11,  15,   4, -17,
+    -12,  17,  14,  17,  17,  38,  23,  11,
+     10,  17,  23,  15,  20,  45,  44,  13,
+     -8,  22,  24,  27,  26,  33,  26,   3,
+    -18,  -4,  21,  24,  27,  23,   9, -11,
+    -19,  -3,  11,  21,  23,  16,   7,  -9,
+    -27, -11,   4,  13,  14,   4,  -5, -17,
+    -53, -34, -21, -11, -28, -14, -24, -43,
+];
@@ -1,10 +1,377 @@
+use ::betza::*;
 use ::board::*;
 use ::moves::*;
 use ::piece::*;
 use ::square::*;
 
-pub fn generate_pseudo_legal_moves(board: Board) -> Vec<Move> {
-    let mut res = Vec::new();
+pub fn generate_pseudo_legal_moves(board: &Board) -> MoveList {
+    let mut res = MoveList::new();
+    board.get_variant().pseudo_legal_moves(board, MoveKinds::All, &mut res);
+    res
+}
+
+/// Generates only the moves that don't leave own king in check.
+pub fn generate_legal_moves(mut board: Board) -> MoveList {
+    legal_moves(&mut board)
+}
+
+/// Counts leaf nodes of the legal move tree of a given depth (used for move generator testing).
+pub fn perft(mut board: Board, depth: u32) -> u64 {
+    perft_inner(&mut board, depth)
+}
+
+fn perft_inner(board: &mut Board, depth: u32) -> u64 {
+    let moves = legal_moves(board);
+    if depth <= 1 {
+        return if depth == 1 { moves.len() as u64 } else { 1 };
+    }
+    let mut count = 0;
+    for m in moves {
+        board.do_move_inplace(m).unwrap();
+        count += perft_inner(board, depth - 1);
+        board.undo_move_inplace().unwrap();
+    }
+    count
+}
+
+/// Legal moves for the side to move (none once the variant's special rules end the game).
+/// The board is used for trying the moves, but is left unchanged.
+pub(crate) fn legal_moves(board: &mut Board) -> MoveList {
+    let variant = board.get_variant();
+    if variant.special_outcome(board).is_some() {
+        // the game is over
+        return MoveList::new();
+    }
+    let mut res = generate_pseudo_legal_moves(board);
+    res.retain(|&m| {
+        board.do_move_inplace(m).unwrap();
+        let legal = variant.is_legal(board, m);
+        board.undo_move_inplace().unwrap();
+        legal
+    });
+    variant.filter_legal_moves(board, &mut res);
+    res
+}
+
+/// Kinds of moves to generate, see `Variant::pseudo_legal_moves()`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MoveKinds {
+    All,
+    /// Moves capturing a piece (en passant included).
+    Captures,
+    /// Moves capturing nothing (castling, drops and promotions included).
+    Quiets,
+    /// Promotions capturing nothing.
+    Promotions,
+}
+
+impl MoveKinds {
+    pub fn has_captures(&self) -> bool {
+        matches!(*self, MoveKinds::All | MoveKinds::Captures)
+    }
+
+    /// Returns `true` for all the moves capturing nothing, not only the promotions.
+    pub fn has_quiets(&self) -> bool {
+        matches!(*self, MoveKinds::All | MoveKinds::Quiets)
+    }
+
+    /// Returns `true` if promotions capturing nothing are included.
+    pub fn has_promotions(&self) -> bool {
+        *self != MoveKinds::Captures
+    }
+
+    /// Returns `true` if the move is of these kinds.
+    pub fn contains(&self, m: Move) -> bool {
+        if m.get_captured_piece().is_some() {
+            self.has_captures()
+        } else if m.get_promoted_to().is_some() {
+            self.has_promotions()
+        } else {
+            self.has_quiets()
+        }
+    }
+}
+
+/// Moves yielded by a `MoveGen`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MoveFilter {
+    All,
+    /// Moves capturing a piece (en passant included).
+    Captures,
+    /// Moves capturing nothing (castling, drops and promotions included).
+    Quiets,
+    /// Promotions capturing nothing.
+    Promotions,
+    /// Moves giving check.
+    Checks,
+    /// Moves of the piece on the square.
+    From(Square),
+    /// Moves and drops to the square.
+    To(Square),
+}
+
+impl MoveFilter {
+    /// Returns `true` if the pseudo-legal move of the position passes the filter.
+    /// Checks are found by making and undoing the move, the board is left as it was.
+    pub fn accepts(&self, board: &mut Board, m: Move) -> bool {
+        match *self {
+            MoveFilter::All => true,
+            MoveFilter::Captures => m.get_captured_piece().is_some(),
+            MoveFilter::Quiets => m.get_captured_piece().is_none(),
+            MoveFilter::Promotions => {
+                m.get_captured_piece().is_none() && m.get_promoted_to().is_some()
+            }
+            MoveFilter::Checks => {
+                if board.do_move_inplace(m).is_err() {
+                    return false;
+                }
+                let check = board.is_in_check();
+                board.undo_move_inplace().unwrap();
+                check
+            }
+            MoveFilter::From(s) => !m.is_drop() && m.get_square_from() == s,
+            MoveFilter::To(s) => m.get_square_to() == s,
+        }
+    }
+
+    /// Kinds of moves that may pass the filter.
+    fn kinds(&self) -> MoveKinds {
+        match *self {
+            MoveFilter::Captures => MoveKinds::Captures,
+            MoveFilter::Quiets => MoveKinds::Quiets,
+            MoveFilter::Promotions => MoveKinds::Promotions,
+            _ => MoveKinds::All,
+        }
+    }
+}
+
+/// Stages of a `MoveGen`, in the order they run.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Stage {
+    HashMove,
+    /// Captures not losing material by Static Exchange Evaluation, most valuable victim
+    /// (and least valuable attacker) first.
+    GoodCaptures,
+    Killers,
+    /// Moves capturing nothing, promotions first.
+    Quiets,
+    /// Captures losing material, in the same order as the good ones.
+    BadCaptures,
+    Done,
+}
+
+/// Staged generator of the pseudo-legal moves of a position, ordered for searching.
+/// The hash move and the killers are tried without generating moves, the captures are
+/// generated when the good captures are reached and the quiet moves only after the killers,
+/// so a cutoff early in the list saves generating the rest.
+/// Compulsory captures of the variant are applied, legality is left to the caller.
+///
+/// The generator doesn't hold the board: `next_move()` takes it on each call, so the caller
+/// may make and undo moves in between. It must be the same position every time.
+pub struct MoveGen {
+    filter: MoveFilter,
+    hash_move: Option<Move>,
+    killers: [Option<Move>; 2],
+    stage: Stage,
+    moves: MoveList,
+    /// The buffer holds the captures, then the promotions, then the other quiet moves.
+    promotions_start: usize,
+    quiets_start: usize,
+    /// Start of the captures found losing, moved to the end of the captures.
+    bad_start: usize,
+    /// Position of the next move (or killer) of the current stage.
+    next: usize,
+}
+
+impl MoveGen {
+    pub fn new(filter: MoveFilter) -> MoveGen {
+        MoveGen {
+            filter,
+            hash_move: None,
+            killers: [None; 2],
+            stage: Stage::HashMove,
+            moves: MoveList::new(),
+            promotions_start: 0,
+            quiets_start: 0,
+            bad_start: 0,
+            next: 0,
+        }
+    }
+
+    /// Sets the move to try first, usually the best move from the transposition table.
+    /// It is checked to be pseudo-legal in the position.
+    pub fn with_hash_move(mut self, m: Option<Move>) -> MoveGen {
+        self.hash_move = m;
+        self
+    }
+
+    /// Sets the quiet moves to try right after the good captures.
+    /// They are checked to be pseudo-legal in the position.
+    pub fn with_killers(mut self, killers: [Option<Move>; 2]) -> MoveGen {
+        self.killers = killers;
+        self
+    }
+
+    /// Returns the stage of the last move yielded.
+    pub fn get_stage(&self) -> Stage {
+        self.stage
+    }
+
+    /// Generates the moves of the given kinds that pass the filter to the end of the buffer.
+    fn generate(&mut self, board: &mut Board, kinds: MoveKinds) {
+        let start = self.moves.len();
+        let variant = board.get_variant();
+        let mut moves = MoveList::new();
+        variant.pseudo_legal_moves(board, kinds, &mut moves);
+        variant.filter_legal_moves(board, &mut moves);
+        let filter = self.filter;
+        for m in moves {
+            if filter == MoveFilter::All || filter.accepts(board, m) {
+                self.moves.push(m);
+            }
+        }
+        for i in start..self.moves.len() {
+            let m = self.moves[i];
+            let score = if m.get_captured_piece().is_some() {
+                mvv_lva(m)
+            } else {
+                m.get_promoted_to().map_or(0, see_value)
+            };
+            self.moves.set_score(i, score);
+        }
+    }
+
+    /// Swaps the best scored move of `moves[start..end]` to `start`.
+    fn pick_best(&mut self, start: usize, end: usize) -> Move {
+        let best = (start..end).max_by_key(|&i| self.moves.get_score(i)).unwrap();
+        self.moves.swap(start, best);
+        self.moves[start]
+    }
+
+    fn is_killer(&self, m: Move) -> bool {
+        self.killers.contains(&Some(m))
+    }
+
+    /// Returns the next move, or `None` when all are done.
+    pub fn next_move(&mut self, board: &mut Board) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    if self.next == 0 {
+                        self.next = 1;
+                        if let Some(m) = self.hash_move {
+                            if board.is_pseudo_legal(m) && self.filter.accepts(board, m) {
+                                return Some(m);
+                            }
+                        }
+                    }
+                    if self.filter.kinds().has_captures() {
+                        self.generate(board, MoveKinds::Captures);
+                    }
+                    self.promotions_start = self.moves.len();
+                    self.bad_start = self.moves.len();
+                    self.stage = Stage::GoodCaptures;
+                    self.next = 0;
+                }
+                Stage::GoodCaptures => {
+                    if self.next >= self.bad_start {
+                        self.stage = Stage::Killers;
+                        self.next = 0;
+                        continue;
+                    }
+                    let m = self.pick_best(self.next, self.bad_start);
+                    if Some(m) == self.hash_move {
+                        self.next += 1;
+                    } else if !board.see_ge(m, 0) {
+                        self.bad_start -= 1;
+                        self.moves.swap(self.next, self.bad_start);
+                    } else {
+                        self.next += 1;
+                        return Some(m);
+                    }
+                }
+                Stage::Killers => {
+                    if self.next >= self.killers.len() {
+                        self.stage = Stage::Quiets;
+                        match self.filter.kinds() {
+                            MoveKinds::Captures => {}
+                            MoveKinds::Promotions => self.generate(board, MoveKinds::Promotions),
+                            _ => self.generate(board, MoveKinds::Quiets),
+                        }
+                        self.quiets_start = partition(&mut self.moves,
+                                                      self.promotions_start,
+                                                      |m| m.get_promoted_to().is_some());
+                        self.next = self.promotions_start;
+                        continue;
+                    }
+                    let killer = self.killers[self.next];
+                    self.next += 1;
+                    if let Some(m) = killer {
+                        let repeated = self.next == 2 && self.killers[0] == killer;
+                        if Some(m) != self.hash_move && !repeated &&
+                           m.get_captured_piece().is_none() && board.is_pseudo_legal(m) &&
+                           self.filter.accepts(board, m) {
+                            return Some(m);
+                        }
+                    }
+                }
+                Stage::Quiets => {
+                    if self.next >= self.moves.len() {
+                        self.stage = Stage::BadCaptures;
+                        self.next = self.bad_start;
+                        continue;
+                    }
+                    let m = if self.next < self.quiets_start {
+                        self.pick_best(self.next, self.quiets_start)
+                    } else {
+                        self.moves[self.next]
+                    };
+                    self.next += 1;
+                    if Some(m) != self.hash_move && !self.is_killer(m) {
+                        return Some(m);
+                    }
+                }
+                Stage::BadCaptures => {
+                    if self.next >= self.promotions_start {
+                        self.stage = Stage::Done;
+                        continue;
+                    }
+                    let m = self.pick_best(self.next, self.promotions_start);
+                    self.next += 1;
+                    return Some(m);
+                }
+                Stage::Done => return None,
+            }
+        }
+    }
+}
+
+/// Moves the moves matching `pred` in `moves[start..]` to the front and returns where
+/// the others start.
+fn partition<F: Fn(Move) -> bool>(moves: &mut MoveList, start: usize, pred: F) -> usize {
+    let mut end = start;
+    for i in start..moves.len() {
+        if pred(moves[i]) {
+            moves.swap(i, end);
+            end += 1;
+        }
+    }
+    end
+}
+
+/// Most valuable victim, least valuable attacker (promotions add to the victim).
+fn mvv_lva(m: Move) -> i32 {
+    let victim = m.get_captured_piece().map_or(0, |p| see_value(p.get_kind()));
+    let promotion = m.get_promoted_to().map_or(0, |k| see_value(k) - see_value(PieceKind::Pawn));
+    (victim + promotion) * 1000 - see_value(m.get_moving_piece().get_kind())
+}
+
+/// Kinds pawns may promote to in standard chess.
+pub const STANDARD_PROMOTIONS: [PieceKind; 4] = [PieceKind::Knight, PieceKind::Bishop,
+                                                 PieceKind::Rook, PieceKind::Queen];
+
+/// Pseudo-legal moves of the given kinds by standard chess rules, for use by variants.
+pub fn standard_pseudo_legal_moves(board: &Board, kinds: MoveKinds, res: &mut MoveList) {
     let rook_dirs = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
     let bishop_dirs = [Direction::UpLeft,
         Direction::UpRight,
@@ -18,32 +385,39 @@ pub fn generate_pseudo_legal_moves(board: Board) -> Vec<Move> {
         Direction::UpRight,
         Direction::DownLeft,
         Direction::DownRight];
-    for i in 0..64 {
-        let s = Square::from_int(i);
+    let (captures, quiets) = (kinds.has_captures(), kinds.has_quiets());
+    let promotions = kinds.has_promotions();
+    let pawn_captures: &[Direction] = if captures {
+        &[Direction::Left, Direction::Right]
+    } else {
+        &[]
+    };
+    let geometry = board.get_geometry();
+    for s in geometry.squares() {
         match board.get_piece(s) {
             Some(p) if p.get_color() == board.get_side_to_move() => {
                 match p.get_kind() {
                     PieceKind::Pawn => {
-                        let t1 = s.get_forward(p.get_color()).unwrap();
-                        let t2 = t1.get_forward(p.get_color());
-                        if t2.is_some() {
-                            let t2 = t2.unwrap();
+                        // pawns never stand on the rank they promote on
+                        let t1 = match s.get_forward_in(p.get_color(), geometry) {
+                            Some(t) => t,
+                            None => continue,
+                        };
+                        let t2 = t1.get_forward_in(p.get_color(), geometry);
+                        if let Some(t2) = t2 {
                             // no need to promotion
-                            if let None = board.get_piece(t1) {
+                            if quiets && board.get_piece(t1).is_none() {
                                 let m = Move::new(p, s, t1, None, None, false, false);
                                 res.push(m);
-                                if (s.get_rank() == Rank::Second &&
-                                    p.get_color() == Color::White) ||
-                                    (s.get_rank() == Rank::Seventh &&
-                                        p.get_color() == Color::Black) &&
-                                        board.get_piece(t2).is_none() {
+                                if s.get_rank() == geometry.relative_rank(p.get_color(), 1) &&
+                                   board.get_piece(t2).is_none() {
                                     // move 2 squares from starting position
                                     let m = Move::new(p, s, t2, None, None, false, false);
                                     res.push(m);
                                 }
                             }
-                            for d in [Direction::Left, Direction::Right].iter() {
-                                if let Some(t) = t1.get_by_dir(*d) {
+                            for d in pawn_captures.iter() {
+                                if let Some(t) = t1.get_by_dir_in(*d, geometry) {
                                     match board.get_piece(t) {
                                         Some(piece) if piece.get_color() != p.get_color() => {
                                             let m = Move::new(p,
@@ -55,22 +429,30 @@ pub fn generate_pseudo_legal_moves(board: Board) -> Vec<Move> {
                                                               false);
                                             res.push(m);
                                         }
+                                        None if board.get_en_passant_square() == Some(t) => {
+                                            let captured = Piece::new(PieceKind::Pawn,
+                                                                      p.get_color().opposite());
+                                            let m = Move::new(p,
+                                                              s,
+                                                              t,
+                                                              Some(captured),
+                                                              None,
+                                                              true,
+                                                              false);
+                                            res.push(m);
+                                        }
                                         _ => {}
                                     }
                                 }
                             }
                         } else {
-                            for pr in [PieceKind::Knight,
-                                PieceKind::Bishop,
-                                PieceKind::Rook,
-                                PieceKind::Queen]
-                                .iter() {
-                                if let None = board.get_piece(t1) {
+                            for pr in STANDARD_PROMOTIONS.iter() {
+                                if promotions && board.get_piece(t1).is_none() {
                                     let m = Move::new(p, s, t1, None, Some(*pr), false, false);
                                     res.push(m);
                                 }
-                                for d in [Direction::Left, Direction::Right].iter() {
-                                    if let Some(t) = t1.get_by_dir(*d) {
+                                for d in pawn_captures.iter() {
+                                    if let Some(t) = t1.get_by_dir_in(*d, geometry) {
                                         match board.get_piece(t) {
                                             Some(piece) if piece.get_color() != p.get_color() => {
                                                 let m = Move::new(p,
@@ -89,38 +471,43 @@ pub fn generate_pseudo_legal_moves(board: Board) -> Vec<Move> {
                             }
                         }
                     }
-                    PieceKind::Knight => {
-                        for t in s.get_knight_moves() {
+                    PieceKind::Knight | PieceKind::Bishop | PieceKind::Rook | PieceKind::Queen |
+                    PieceKind::Archbishop | PieceKind::Chancellor => {
+                        let leaps = match p.get_kind() {
+                            PieceKind::Knight | PieceKind::Archbishop | PieceKind::Chancellor => {
+                                Some(s.iter_knight_moves_in(geometry))
+                            }
+                            _ => None,
+                        };
+                        for t in leaps.into_iter().flatten() {
                             if let Some(piece) = board.get_piece(t) {
-                                if piece.get_color() != p.get_color() {
+                                if captures && piece.get_color() != p.get_color() {
                                     let m = Move::new(p, s, t, Some(piece), None, false, false);
                                     res.push(m);
                                 }
-                            } else {
+                            } else if quiets {
                                 let m = Move::new(p, s, t, None, None, false, false);
                                 res.push(m);
                             }
                         }
-                    }
-                    PieceKind::Bishop | PieceKind::Rook | PieceKind::Queen => {
                         let dirs: &[Direction] = match p.get_kind() {
-                            PieceKind::Bishop => &bishop_dirs,
-                            PieceKind::Rook => &rook_dirs,
+                            PieceKind::Bishop | PieceKind::Archbishop => &bishop_dirs,
+                            PieceKind::Rook | PieceKind::Chancellor => &rook_dirs,
                             PieceKind::Queen => &queen_dirs,
-                            _ => unreachable!(),
+                            _ => &[],
                         };
                         let mut t;
                         for d in dirs {
                             t = s;
-                            while let Some(u) = t.get_by_dir(*d) {
+                            while let Some(u) = t.get_by_dir_in(*d, geometry) {
                                 t = u;
                                 if let Some(piece) = board.get_piece(t) {
-                                    if piece.get_color() != p.get_color() {
+                                    if captures && piece.get_color() != p.get_color() {
                                         let m = Move::new(p, s, t, Some(piece), None, false, false);
                                         res.push(m);
                                     }
                                     break;
-                                } else {
+                                } else if quiets {
                                     let m = Move::new(p, s, t, None, None, false, false);
                                     res.push(m);
                                 }
@@ -129,13 +516,13 @@ pub fn generate_pseudo_legal_moves(board: Board) -> Vec<Move> {
                     }
                     PieceKind::King => {
                         for d in &queen_dirs {
-                            if let Some(t) = s.get_by_dir(*d) {
+                            if let Some(t) = s.get_by_dir_in(*d, geometry) {
                                 if let Some(piece) = board.get_piece(t) {
-                                    if piece.get_color() != p.get_color() {
+                                    if captures && piece.get_color() != p.get_color() {
                                         let m = Move::new(p, s, t, Some(piece), None, false, false);
                                         res.push(m);
                                     }
-                                } else {
+                                } else if quiets {
                                     let m = Move::new(p, s, t, None, None, false, false);
                                     res.push(m);
                                 }
@@ -147,6 +534,252 @@ pub fn generate_pseudo_legal_moves(board: Board) -> Vec<Move> {
             _ => {}
         }
     }
-    // TODO: Castling and en passant
-    res
+    if quiets {
+        generate_castlings(board, res);
+    }
+}
+
+/// Returns `true` if `m` is a pseudo-legal move by standard chess rules, with pawns promoting
+/// to `promotions` (and drops of pieces in the pocket). It doesn't generate the moves,
+/// so it is fast enough for validating moves from the transposition table; for use by variants.
+pub fn standard_is_pseudo_legal(board: &Board, m: Move, promotions: &[PieceKind]) -> bool {
+    let p = m.get_moving_piece();
+    let color = p.get_color();
+    let from = m.get_square_from();
+    let to = m.get_square_to();
+    let geometry = board.get_geometry();
+    if color != board.get_side_to_move() || !geometry.contains(from) || !geometry.contains(to) {
+        return false;
+    }
+    let captured = m.get_captured_piece();
+    if m.is_drop() {
+        let pawn_rank = to.get_rank() == Rank::First || to.get_rank() == geometry.get_last_rank();
+        return board.get_pocket(color, p.get_kind()) > 0 && board.get_piece(to).is_none() &&
+               !(p.get_kind() == PieceKind::Pawn && pawn_rank);
+    }
+    if board.get_piece(from) != Some(p) {
+        return false;
+    }
+    if m.is_castling() {
+        let mut castlings = MoveList::new();
+        generate_castlings(board, &mut castlings);
+        return castlings.contains(&m);
+    }
+    if m.is_en_passant() {
+        if p.get_kind() != PieceKind::Pawn || board.get_en_passant_square() != Some(to) ||
+           captured != Some(Piece::new(PieceKind::Pawn, color.opposite())) {
+            return false;
+        }
+    } else if board.get_piece(to) != captured || captured.is_some_and(|c| c.get_color() == color) {
+        return false;
+    }
+    let files = to.get_file() as i32 - from.get_file() as i32;
+    let ranks = to.get_rank() as i32 - from.get_rank() as i32;
+    let kind = p.get_kind();
+    if kind == PieceKind::Pawn {
+        let forward = if color == Color::White { 1 } else { -1 };
+        let promotes = to.get_rank() == geometry.back_rank(color.opposite());
+        if promotes != m.get_promoted_to().is_some() ||
+           m.get_promoted_to().is_some_and(|k| !promotions.contains(&k)) {
+            return false;
+        }
+        return if files.abs() == 1 {
+            ranks == forward && captured.is_some()
+        } else if files != 0 || captured.is_some() {
+            false
+        } else {
+            ranks == forward ||
+            ranks == 2 * forward && from.get_rank() == geometry.relative_rank(color, 1) &&
+            board.get_piece(from.get_forward_in(color, geometry).unwrap()).is_none()
+        };
+    }
+    if m.get_promoted_to().is_some() || m.is_en_passant() || (files, ranks) == (0, 0) {
+        return false;
+    }
+    let (far, near) = (files.abs().max(ranks.abs()), files.abs().min(ranks.abs()));
+    let leaper = match kind {
+        PieceKind::Knight | PieceKind::Archbishop | PieceKind::Chancellor => (far, near) == (2, 1),
+        PieceKind::King => far == 1,
+        _ => false,
+    };
+    let slider = match kind {
+        PieceKind::Bishop | PieceKind::Archbishop => near == far,
+        PieceKind::Rook | PieceKind::Chancellor => near == 0,
+        PieceKind::Queen => near == far || near == 0,
+        _ => false,
+    };
+    if leaper {
+        return true;
+    }
+    if !slider {
+        return false;
+    }
+    // the squares in between must be empty
+    let mut s = from;
+    for _ in 1..far {
+        s = s.get_by_offset(files.signum(), ranks.signum(), geometry).unwrap();
+        if board.get_piece(s).is_some() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Drops of pieces in hand on empty squares (pawns not on the first or the last rank),
+/// for use by variants.
+pub fn drop_moves(board: &Board, res: &mut MoveList) {
+    let color = board.get_side_to_move();
+    let kinds = [PieceKind::Pawn, PieceKind::Knight, PieceKind::Bishop, PieceKind::Rook,
+                 PieceKind::Queen];
+    let geometry = board.get_geometry();
+    for &kind in kinds.iter().filter(|&&k| board.get_pocket(color, k) > 0) {
+        for s in geometry.squares() {
+            if board.get_piece(s).is_some() ||
+               (kind == PieceKind::Pawn && (s.get_rank() == Rank::First ||
+                                            s.get_rank() == geometry.get_last_rank())) {
+                continue;
+            }
+            res.push(Move::new_drop(Piece::new(kind, color), s));
+        }
+    }
+}
+
+/// Castling moves are encoded as the king moving to the G or C file (even in Chess960,
+/// see `Board::castling_files()` for wider boards).
+/// Squares passed by the king are checked for attacks here, since it can't be done afterwards.
+fn generate_castlings(board: &Board, res: &mut MoveList) {
+    let color = board.get_side_to_move();
+    let rank = board.get_geometry().back_rank(color);
+    let (kingside, queenside) = match color {
+        Color::White => (CastlingRights::WhiteKingside, CastlingRights::WhiteQueenside),
+        Color::Black => (CastlingRights::BlackKingside, CastlingRights::BlackQueenside),
+    };
+    let king = Piece::new(PieceKind::King, color);
+    let king_from = board.get_king_square(color);
+    let variant = board.get_variant();
+    let sides = [(kingside, board.get_initial_kings_rook_file(color), board.castling_files(true)),
+                 (queenside, board.get_initial_queens_rook_file(color), board.castling_files(false))];
+    for &(right, rook_file, (king_file, rook_to_file)) in &sides {
+        if !board.can_castle(right) || king_from.get_rank() != rank {
+            continue;
+        }
+        let rook_from = Square::from_file_and_rank(rook_file, rank);
+        if board.get_piece(rook_from) != Some(Piece::new(PieceKind::Rook, color)) {
+            continue;
+        }
+        let king_to = Square::from_file_and_rank(king_file, rank);
+        let files = [king_from.get_file(), king_file, rook_file, rook_to_file];
+        let min = *files.iter().min().unwrap() as u32;
+        let max = *files.iter().max().unwrap() as u32;
+        let blocked = (min..max + 1).any(|f| {
+            let s = Square::from_file_and_rank(File::from_int(f), rank);
+            s != king_from && s != rook_from && board.get_piece(s).is_some()
+        });
+        if blocked {
+            continue;
+        }
+        let (a, b) = if king_from.get_file() <= king_file {
+            (king_from.get_file() as u32, king_file as u32)
+        } else {
+            (king_file as u32, king_from.get_file() as u32)
+        };
+        let attacked = (a..b + 1).any(|f| {
+            let s = Square::from_file_and_rank(File::from_int(f), rank);
+            variant.is_king_square_attacked(board, s, color)
+        });
+        if !attacked {
+            res.push(Move::new(king, king_from, king_to, None, None, false, true));
+        }
+    }
+}
+
+/// Adds the pseudo-legal moves of the piece on `from` moving by fairy rules (see `betza`),
+/// for use by variants.
+pub fn fairy_moves(board: &Board, from: Square, rules: &PieceRules, res: &mut MoveList) {
+    let p = match board.get_piece(from) {
+        Some(p) => p,
+        None => return,
+    };
+    let geometry = board.get_geometry();
+    let sign = if p.get_color() == Color::White { 1 } else { -1 };
+    for leap in rules.get_leaps() {
+        // Black's pieces move rotated by half a turn
+        let (files, ranks) = (sign * leap.get_files(), sign * leap.get_ranks());
+        let mut t = from;
+        for _ in 0..leap.get_range() {
+            t = match t.get_by_offset(files, ranks, geometry) {
+                Some(u) => u,
+                None => break,
+            };
+            match board.get_piece(t) {
+                Some(piece) => {
+                    if piece.get_color() != p.get_color() && leap.get_mode() != LeapMode::Move {
+                        res.push(Move::new(p, from, t, Some(piece), None, false, false));
+                    }
+                    break;
+                }
+                None => {
+                    if leap.get_mode() != LeapMode::Capture {
+                        res.push(Move::new(p, from, t, None, None, false, false));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns `true` if `m` is one of the moves `fairy_moves()` generates for the moving piece.
+pub fn fairy_is_pseudo_legal(board: &Board, m: Move, rules: &PieceRules) -> bool {
+    let p = m.get_moving_piece();
+    let from = m.get_square_from();
+    let to = m.get_square_to();
+    let geometry = board.get_geometry();
+    let captured = m.get_captured_piece();
+    if m.is_drop() || m.is_castling() || m.is_en_passant() || m.get_promoted_to().is_some() ||
+       p.get_color() != board.get_side_to_move() || !geometry.contains(from) ||
+       !geometry.contains(to) || board.get_piece(from) != Some(p) ||
+       board.get_piece(to) != captured || captured.is_some_and(|c| c.get_color() == p.get_color()) {
+        return false;
+    }
+    let excluded = if captured.is_some() { LeapMode::Move } else { LeapMode::Capture };
+    fairy_reaches(board, from, p.get_color(), rules, to, excluded)
+}
+
+/// Returns `true` if a piece of `color` on `from` moving by fairy rules attacks `target`.
+pub fn fairy_attacks(board: &Board,
+                     from: Square,
+                     color: Color,
+                     rules: &PieceRules,
+                     target: Square)
+                     -> bool {
+    fairy_reaches(board, from, color, rules, target, LeapMode::Move)
+}
+
+/// Returns `true` if a leap other than the `excluded` mode takes the piece to `target`.
+fn fairy_reaches(board: &Board,
+                 from: Square,
+                 color: Color,
+                 rules: &PieceRules,
+                 target: Square,
+                 excluded: LeapMode)
+                 -> bool {
+    let geometry = board.get_geometry();
+    let sign = if color == Color::White { 1 } else { -1 };
+    rules.get_leaps().iter().filter(|l| l.get_mode() != excluded).any(|leap| {
+        let (files, ranks) = (sign * leap.get_files(), sign * leap.get_ranks());
+        let mut t = from;
+        for _ in 0..leap.get_range() {
+            t = match t.get_by_offset(files, ranks, geometry) {
+                Some(u) => u,
+                None => return false,
+            };
+            if t == target {
+                return true;
+            }
+            if board.get_piece(t).is_some() {
+                return false;
+            }
+        }
+        false
+    })
 }
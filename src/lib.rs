@@ -3,3 +3,15 @@ pub mod square;
 pub mod piece;
 pub mod moves;
 pub mod generator;
+pub mod eval;
+pub mod search;
+pub mod timeman;
+pub mod problems;
+pub mod chess960;
+pub mod pgn;
+pub mod variant;
+pub mod bughouse;
+pub mod betza;
+pub mod error;
+mod tt;
+mod zobrist;
@@ -1,7 +1,12 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Deref;
+use std::slice;
+
 use ::square::*;
 use ::piece::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Move {
     moving_piece: Piece,
     from: Square,
@@ -10,6 +15,7 @@ pub struct Move {
     promoted_to: Option<PieceKind>,
     en_passant: bool,
     castling: bool,
+    drop: bool,
 }
 
 impl Move {
@@ -22,13 +28,29 @@ impl Move {
                castling: bool)
                -> Move {
         Move {
-            moving_piece: moving_piece,
-            from: from,
-            to: to,
-            captured_piece: captured_piece,
-            promoted_to: promoted_to,
-            en_passant: en_passant,
-            castling: castling,
+            moving_piece,
+            from,
+            to,
+            captured_piece,
+            promoted_to,
+            en_passant,
+            castling,
+            drop: false,
+        }
+    }
+
+    /// Constructs a move dropping a piece from the pocket (Crazyhouse), written as `N@f3`.
+    /// Both its starting and target squares are `to`.
+    pub fn new_drop(piece: Piece, to: Square) -> Move {
+        Move {
+            moving_piece: piece,
+            from: to,
+            to,
+            captured_piece: None,
+            promoted_to: None,
+            en_passant: false,
+            castling: false,
+            drop: true,
         }
     }
 
@@ -53,7 +75,295 @@ impl Move {
     pub fn is_castling(&self) -> bool {
         self.castling
     }
+    pub fn is_drop(&self) -> bool {
+        self.drop
+    }
     pub fn is_normal_move(&self) -> bool {
-        !(self.castling || self.en_passant)
+        !(self.castling || self.en_passant || self.drop)
     }
 }
+
+/// A move packed into 16 bits, for transposition tables, opening books and training data:
+/// the starting and the target square (6 bits each, so boards up to 8x8 only) and 4 bits
+/// for the promotion, castling, en passant or the dropped piece. The pieces are restored
+/// from the position by `Board::expand()`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PackedMove(u16);
+
+const CASTLING_FLAG: u16 = 8;
+const EN_PASSANT_FLAG: u16 = 9;
+/// Drops are flagged by this plus the kind (up to the king).
+const DROP_FLAG: u16 = 10;
+
+impl PackedMove {
+    /// Packs a move, or returns `None` if it can't be packed (squares outside the 8x8 board
+    /// or a drop of a compound piece).
+    pub fn new(m: Move) -> Option<PackedMove> {
+        let number = |s: Square| s.to_int().map(|n| n as u16);
+        let flags = if m.is_drop() {
+            DROP_FLAG + m.get_moving_piece().get_kind() as u16
+        } else if m.is_castling() {
+            CASTLING_FLAG
+        } else if m.is_en_passant() {
+            EN_PASSANT_FLAG
+        } else {
+            m.get_promoted_to().map_or(0, |kind| kind as u16)
+        };
+        if flags > 15 {
+            return None;
+        }
+        Some(PackedMove(number(m.get_square_from())? | number(m.get_square_to())? << 6 |
+                        flags << 12))
+    }
+
+    pub fn from_u16(bits: u16) -> PackedMove {
+        PackedMove(bits)
+    }
+
+    pub fn to_u16(&self) -> u16 {
+        self.0
+    }
+
+    fn flags(&self) -> u16 {
+        self.0 >> 12
+    }
+
+    pub fn get_square_from(&self) -> Square {
+        Square::from_int((self.0 & 63) as u32)
+    }
+
+    pub fn get_square_to(&self) -> Square {
+        Square::from_int((self.0 >> 6 & 63) as u32)
+    }
+
+    pub fn get_promoted_to(&self) -> Option<PieceKind> {
+        match self.flags() {
+            f @ 1..=7 => Some(ALL_KINDS[f as usize]),
+            _ => None,
+        }
+    }
+
+    pub fn get_dropped_kind(&self) -> Option<PieceKind> {
+        if self.flags() >= DROP_FLAG {
+            Some(ALL_KINDS[(self.flags() - DROP_FLAG) as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn is_castling(&self) -> bool {
+        self.flags() == CASTLING_FLAG
+    }
+
+    pub fn is_en_passant(&self) -> bool {
+        self.flags() == EN_PASSANT_FLAG
+    }
+}
+
+/// Number of moves a `MoveList` holds without allocating.
+pub const MAX_MOVES: usize = 256;
+
+/// List of moves, each with a score for ordering. Up to `MAX_MOVES` moves are stored inline,
+/// so generating moves doesn't allocate. Longer lists (possible with drops) move to the heap.
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    scores: [i32; MAX_MOVES],
+    len: usize,
+    spilled: Option<(Vec<Move>, Vec<i32>)>,
+}
+
+impl MoveList {
+    pub fn new() -> MoveList {
+        let filler = Move::new_drop(Piece::new(PieceKind::Pawn, Color::White), Square::from_int(0));
+        MoveList {
+            moves: [filler; MAX_MOVES],
+            scores: [0; MAX_MOVES],
+            len: 0,
+            spilled: None,
+        }
+    }
+
+    /// Adds a move scored 0.
+    pub fn push(&mut self, m: Move) {
+        self.push_scored(m, 0);
+    }
+
+    pub fn push_scored(&mut self, m: Move, score: i32) {
+        if self.spilled.is_none() && self.len == MAX_MOVES {
+            self.spilled = Some((self.moves.to_vec(), self.scores.to_vec()));
+        }
+        match self.spilled {
+            Some((ref mut moves, ref mut scores)) => {
+                moves.push(m);
+                scores.push(score);
+            }
+            None => {
+                self.moves[self.len] = m;
+                self.scores[self.len] = score;
+                self.len += 1;
+            }
+        }
+    }
+
+    fn parts_mut(&mut self) -> (&mut [Move], &mut [i32]) {
+        match self.spilled {
+            Some((ref mut moves, ref mut scores)) => (moves, scores),
+            None => (&mut self.moves[..self.len], &mut self.scores[..self.len]),
+        }
+    }
+
+    fn truncate(&mut self, len: usize) {
+        match self.spilled {
+            Some((ref mut moves, ref mut scores)) => {
+                moves.truncate(len);
+                scores.truncate(len);
+            }
+            None => self.len = self.len.min(len),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.spilled = None;
+    }
+
+    /// Keeps only the moves for which `f` returns `true`, in order and with their scores.
+    pub fn retain<F: FnMut(&Move) -> bool>(&mut self, mut f: F) {
+        let mut kept = 0;
+        {
+            let (moves, scores) = self.parts_mut();
+            for i in 0..moves.len() {
+                if f(&moves[i]) {
+                    moves[kept] = moves[i];
+                    scores[kept] = scores[i];
+                    kept += 1;
+                }
+            }
+        }
+        self.truncate(kept);
+    }
+
+    pub fn get_score(&self, i: usize) -> i32 {
+        match self.spilled {
+            Some((_, ref scores)) => scores[i],
+            None => self.scores[..self.len][i],
+        }
+    }
+
+    pub fn set_score(&mut self, i: usize, score: i32) {
+        self.parts_mut().1[i] = score;
+    }
+
+    /// Scores every move by `f`.
+    pub fn score_moves<F: FnMut(Move) -> i32>(&mut self, mut f: F) {
+        let (moves, scores) = self.parts_mut();
+        for (m, score) in moves.iter().zip(scores.iter_mut()) {
+            *score = f(*m);
+        }
+    }
+
+    /// Swaps two moves with their scores.
+    pub fn swap(&mut self, i: usize, j: usize) {
+        let (moves, scores) = self.parts_mut();
+        moves.swap(i, j);
+        scores.swap(i, j);
+    }
+
+    /// Sorts the moves by score, highest first. Equal scores keep their order.
+    pub fn sort_by_score(&mut self) {
+        // insertion sort: the lists are short and it needs no buffer
+        let (moves, scores) = self.parts_mut();
+        for i in 1..moves.len() {
+            let (m, score) = (moves[i], scores[i]);
+            let mut j = i;
+            while j > 0 && scores[j - 1] < score {
+                moves[j] = moves[j - 1];
+                scores[j] = scores[j - 1];
+                j -= 1;
+            }
+            moves[j] = m;
+            scores[j] = score;
+        }
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> MoveList {
+        MoveList::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        match self.spilled {
+            Some((ref moves, _)) => moves,
+            None => &self.moves[..self.len],
+        }
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Extend<Move> for MoveList {
+    fn extend<I: IntoIterator<Item = Move>>(&mut self, iter: I) {
+        for m in iter {
+            self.push(m);
+        }
+    }
+}
+
+impl FromIterator<Move> for MoveList {
+    fn from_iter<I: IntoIterator<Item = Move>>(iter: I) -> MoveList {
+        let mut res = MoveList::new();
+        res.extend(iter);
+        res
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> slice::Iter<'a, Move> {
+        self.iter()
+    }
+}
+
+impl IntoIterator for MoveList {
+    type Item = Move;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter { list: self, next: 0 }
+    }
+}
+
+/// Iterator over the moves of a `MoveList`, taken by value.
+pub struct IntoIter {
+    list: MoveList,
+    next: usize,
+}
+
+impl Iterator for IntoIter {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        let m = self.list.get(self.next).cloned();
+        self.next += 1;
+        m
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.list.len().saturating_sub(self.next);
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
@@ -0,0 +1,294 @@
+use ::board::*;
+use ::error::*;
+use ::generator::*;
+use ::moves::*;
+use ::square::*;
+use ::variant::*;
+
+/// A game in Portable Game Notation: tags, starting position and moves.
+#[derive(Clone)]
+pub struct Game {
+    tags: Vec<(String, String)>,
+    start: Board,
+    moves: Vec<Move>,
+}
+
+impl Game {
+    /// Creates a game without moves with the Seven Tag Roster. Starting positions other
+    /// than the variant's own are recorded in the `FEN` tag, variants (including Chess960
+    /// setups) in the `Variant` tag.
+    pub fn new(start: Board) -> Game {
+        let mut game = Game {
+            tags: Vec::new(),
+            start,
+            moves: Vec::new(),
+        };
+        for &(name, value) in &[("Event", "?"), ("Site", "?"), ("Date", "????.??.??"),
+                                ("Round", "?"), ("White", "?"), ("Black", "?"),
+                                ("Result", "*")] {
+            game.set_tag(name, value);
+        }
+        let variant = game.start.get_variant();
+        if variant.name() != STANDARD.name() {
+            game.set_tag("Variant", variant.pgn_name());
+        } else if is_chess960(&game.start) {
+            game.set_tag("Variant", CHESS960.pgn_name());
+        }
+        let fen = game.start.to_fen();
+        if fen != Board::new_variant(variant).to_fen() {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &fen);
+        }
+        game
+    }
+
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|t| t.0 == name).map(|t| t.1.as_str())
+    }
+
+    /// Sets a tag, keeping its position if it exists already.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|t| t.0 == name) {
+            Some(t) => t.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn get_tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    pub fn get_start(&self) -> &Board {
+        &self.start
+    }
+
+    pub fn get_moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Returns the position after all moves.
+    pub fn board(&self) -> Board {
+        let mut b = self.start.clone();
+        for &m in &self.moves {
+            b.do_move_inplace(m).unwrap();
+        }
+        b
+    }
+
+    /// Plays a move at the end of the game.
+    pub fn push(&mut self, m: Move) -> Result<(), MoveError> {
+        if !generate_legal_moves(self.board()).contains(&m) {
+            return Err(MoveError::IllegalMove(m));
+        }
+        self.moves.push(m);
+        Ok(())
+    }
+
+    /// Writes the game, wrapping the movetext at 80 characters.
+    pub fn to_pgn(&self) -> String {
+        let mut res = write_tags(&self.tags);
+        let mut tokens = Vec::new();
+        let mut b = self.start.clone();
+        for (i, &m) in self.moves.iter().enumerate() {
+            if b.get_side_to_move() == Color::White {
+                tokens.push(format!("{}.", b.get_move_number()));
+            } else if i == 0 {
+                tokens.push(format!("{}...", b.get_move_number()));
+            }
+            tokens.push(b.move_to_san(m));
+            b.do_move_inplace(m).unwrap();
+        }
+        tokens.push(self.get_tag("Result").unwrap_or("*").to_string());
+        res.push_str(&wrap_movetext(&tokens));
+        res
+    }
+
+    /// Reads a single game. Comments, variations and annotation glyphs are skipped.
+    pub fn from_pgn(text: &str) -> Result<Game, BoardError> {
+        let (tags, movetext) = read_tags(text)?;
+        let variant = match tags.iter().find(|t: &&(String, String)| t.0 == "Variant") {
+            Some(t) => {
+                variant_by_name(&t.1).ok_or_else(|| BoardError::UnsupportedVariant(t.1.clone()))?
+            }
+            None => &STANDARD,
+        };
+        let start = match tags.iter().find(|t: &&(String, String)| t.0 == "FEN") {
+            Some(t) => Board::from_fen_variant(&t.1, variant)?,
+            None => Board::new_variant(variant),
+        };
+        let mut game = Game {
+            tags,
+            start,
+            moves: Vec::new(),
+        };
+        let mut board = game.start.clone();
+        for token in tokenize(&movetext)? {
+            let word = match token {
+                Token::Word(word) => word,
+                Token::Comment(_) => continue,
+            };
+            // move numbers like `12.` or `12...` (possibly glued to the move)
+            let token = word.rsplit('.').next().unwrap_or("");
+            if token.is_empty() || token.starts_with('$') {
+                continue;
+            }
+            if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token) {
+                if game.get_tag("Result").is_none() {
+                    game.set_tag("Result", token);
+                }
+                break;
+            }
+            let m = board.parse_san(token)
+                .ok_or_else(|| BoardError::IllegalMove(token.to_string()))?;
+            board.do_move_inplace(m)?;
+            game.moves.push(m);
+        }
+        Ok(game)
+    }
+}
+
+/// Chess960 setups other than the standard one (including Double Chess960 setups).
+fn is_chess960(board: &Board) -> bool {
+    let standard = [File::E, File::A, File::H];
+    [Color::White, Color::Black].iter().any(|&c| {
+        [board.get_initial_king_file(c),
+         board.get_initial_queens_rook_file(c),
+         board.get_initial_kings_rook_file(c)] != standard
+    }) || board.chess960_position_number().is_some_and(|n| n != 518)
+}
+
+/// Writes the tag section followed by an empty line.
+pub(crate) fn write_tags(tags: &[(String, String)]) -> String {
+    let mut res = String::new();
+    for (name, value) in tags {
+        let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+        res.push_str(&format!("[{} \"{}\"]\n", name, value));
+    }
+    res.push('\n');
+    res
+}
+
+/// Joins movetext tokens into lines of at most 80 characters.
+pub(crate) fn wrap_movetext(tokens: &[String]) -> String {
+    let mut res = String::new();
+    let mut line = String::new();
+    for t in tokens {
+        if !line.is_empty() && line.len() + 1 + t.len() > 80 {
+            res.push_str(&line);
+            res.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(t);
+    }
+    res.push_str(&line);
+    res.push('\n');
+    res
+}
+
+/// Tag pairs in the order they appear.
+pub(crate) type Tags = Vec<(String, String)>;
+
+/// Reads the tag section, returning the tags and the movetext after them.
+pub(crate) fn read_tags(text: &str) -> Result<(Tags, String), BoardError> {
+    let mut tags = Vec::new();
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.peek().map(|l| l.trim()) {
+        if line.is_empty() {
+            lines.next();
+            continue;
+        }
+        if !line.starts_with('[') {
+            break;
+        }
+        tags.push(parse_tag(line)?);
+        lines.next();
+    }
+    let movetext: Vec<&str> = lines.collect();
+    Ok((tags, movetext.join("\n")))
+}
+
+fn parse_tag(line: &str) -> Result<(String, String), BoardError> {
+    let error = || BoardError::Pgn("Wrong tag", line.to_string());
+    let inner = line.trim_start_matches('[').trim_end_matches(']');
+    let (name, value) = inner.split_at(inner.find(' ').ok_or_else(error)?);
+    let value = value.trim();
+    if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+        return Err(error());
+    }
+    let value = value[1..value.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\");
+    Ok((name.to_string(), value))
+}
+
+/// A part of movetext outside variations.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Token {
+    /// A move (possibly with its number glued on, like `12.e4`), a move number, an annotation
+    /// glyph or the result.
+    Word(String),
+    /// A brace comment, without the braces.
+    Comment(String),
+}
+
+/// Splits movetext into words and brace comments, dropping rest-of-line (`;`) comments
+/// and variations.
+pub(crate) fn tokenize(movetext: &str) -> Result<Vec<Token>, BoardError> {
+    let unbalanced = || BoardError::Pgn("Unbalanced variation", movetext.to_string());
+    let mut tokens = Vec::new();
+    let mut chars = movetext.char_indices().peekable();
+    let mut depth = 0;
+    let mut word = String::new();
+    let flush = |tokens: &mut Vec<Token>, word: &mut String, depth: i32| {
+        if depth == 0 && !word.is_empty() {
+            tokens.push(Token::Word(word.clone()));
+        }
+        word.clear();
+    };
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' => {
+                flush(&mut tokens, &mut word, depth);
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, c)) => comment.push(c),
+                        None => {
+                            return Err(BoardError::Pgn("Unterminated comment",
+                                                       movetext[i..].to_string()))
+                        }
+                    }
+                }
+                if depth == 0 {
+                    tokens.push(Token::Comment(comment));
+                }
+            }
+            ';' => {
+                flush(&mut tokens, &mut word, depth);
+                while chars.peek().is_some_and(|&(_, c)| c != '\n') {
+                    chars.next();
+                }
+            }
+            '(' => {
+                flush(&mut tokens, &mut word, depth);
+                depth += 1;
+            }
+            ')' => {
+                flush(&mut tokens, &mut word, depth);
+                depth -= 1;
+                if depth < 0 {
+                    return Err(unbalanced());
+                }
+            }
+            c if c.is_whitespace() => flush(&mut tokens, &mut word, depth),
+            c => word.push(c),
+        }
+    }
+    flush(&mut tokens, &mut word, depth);
+    if depth != 0 {
+        return Err(unbalanced());
+    }
+    Ok(tokens)
+}
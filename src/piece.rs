@@ -8,7 +8,21 @@ pub enum PieceKind {
     Rook,
     Queen,
     King,
+    /// Moves like a bishop or a knight (Capablanca chess).
+    Archbishop,
+    /// Moves like a rook or a knight (Capablanca chess).
+    Chancellor,
 }
+
+/// Number of piece kinds, for arrays indexed by `PieceKind`.
+pub const KIND_COUNT: usize = 8;
+
+/// All piece kinds, in the order of their indices.
+pub const ALL_KINDS: [PieceKind; KIND_COUNT] = [PieceKind::Pawn, PieceKind::Knight,
+                                                PieceKind::Bishop, PieceKind::Rook,
+                                                PieceKind::Queen, PieceKind::King,
+                                                PieceKind::Archbishop, PieceKind::Chancellor];
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct Piece {
     kind: PieceKind,
@@ -18,8 +32,8 @@ pub struct Piece {
 impl Piece {
     pub fn new(kind: PieceKind, color: Color) -> Piece {
         Piece {
-            kind: kind,
-            color: color,
+            kind,
+            color,
         }
     }
     pub fn get_kind(&self) -> PieceKind {
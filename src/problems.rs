@@ -0,0 +1,193 @@
+use std::fmt;
+
+use ::board::*;
+use ::generator::*;
+use ::moves::*;
+use ::square::*;
+
+/// What the solution has to achieve. The side to move at the start is the attacker
+/// in direct mates and selfmates, and the side that gets mated in helpmates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Stipulation {
+    /// `#N`: the attacker mates in at most N moves against any defence.
+    Mate(u32),
+    /// `s#N`: the attacker forces the defender to give mate in at most N moves.
+    Selfmate(u32),
+    /// `h#N`: both sides cooperate so that the side to move is mated in N moves
+    /// (shorter mates are reported as well, as they cook the problem).
+    Helpmate(u32),
+}
+
+impl fmt::Display for Stipulation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Stipulation::Mate(n) => write!(f, "#{}", n),
+            Stipulation::Selfmate(n) => write!(f, "s#{}", n),
+            Stipulation::Helpmate(n) => write!(f, "h#{}", n),
+        }
+    }
+}
+
+/// Several moves fulfilling the stipulation after the given line of play.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dual {
+    /// Moves from the initial position, starting with the key.
+    pub line: Vec<Move>,
+    pub moves: Vec<Move>,
+}
+
+/// Result of solving a problem.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Solution {
+    /// Every first move that fulfils the stipulation.
+    pub keys: Vec<Move>,
+    /// Every later position where the solving side has more than one way to go on.
+    /// In direct mates and selfmates only the attacker's choices are duals.
+    pub duals: Vec<Dual>,
+}
+
+impl Solution {
+    /// Returns `true` if the stipulation can be fulfilled.
+    pub fn is_solved(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// Returns `true` if there is more than one key, i.e. the problem is cooked.
+    pub fn is_cooked(&self) -> bool {
+        self.keys.len() > 1
+    }
+
+    /// Keys besides the intended one.
+    pub fn cooks(&self, intended: Move) -> Vec<Move> {
+        self.keys.iter().cloned().filter(|&m| m != intended).collect()
+    }
+}
+
+/// Exhaustive solver: every line is examined, without any pruning heuristics.
+struct Solver {
+    stipulation: Stipulation,
+    /// The side that starts.
+    first: Color,
+}
+
+impl Solver {
+    /// The stipulation measured in plies.
+    fn plies(&self) -> u32 {
+        match self.stipulation {
+            Stipulation::Mate(n) => (2 * n).saturating_sub(1),
+            Stipulation::Selfmate(n) | Stipulation::Helpmate(n) => 2 * n,
+        }
+    }
+
+    /// The side that has to give mate.
+    fn mating_side(&self) -> Color {
+        match self.stipulation {
+            Stipulation::Mate(_) => self.first,
+            Stipulation::Selfmate(_) | Stipulation::Helpmate(_) => self.first.opposite(),
+        }
+    }
+
+    /// In direct mates and selfmates the defender's moves must all fail.
+    fn is_defending(&self, board: &Board) -> bool {
+        match self.stipulation {
+            Stipulation::Mate(_) | Stipulation::Selfmate(_) => {
+                board.get_side_to_move() != self.first
+            }
+            Stipulation::Helpmate(_) => false,
+        }
+    }
+
+    /// Checks whether the stipulation is fulfilled from this position within `plies`.
+    fn fulfils(&self, board: &mut Board, plies: u32) -> bool {
+        let moves = legal_moves(board);
+        match board.standard_outcome_with_moves(&moves) {
+            Some(Outcome::Checkmate(winner)) => return winner == self.mating_side(),
+            Some(_) => return false,
+            None => {}
+        }
+        if plies == 0 {
+            return false;
+        }
+        let defending = self.is_defending(board);
+        let mut check = |m: Move| {
+            board.do_move_inplace(m).unwrap();
+            let res = self.fulfils(board, plies - 1);
+            board.undo_move_inplace().unwrap();
+            res
+        };
+        if defending {
+            moves.into_iter().all(&mut check)
+        } else {
+            moves.into_iter().any(&mut check)
+        }
+    }
+
+    /// Moves among the legal `moves` of the side to move that keep the stipulation fulfilled.
+    fn working_moves(&self, board: &mut Board, moves: &[Move], plies: u32) -> Vec<Move> {
+        moves.iter()
+            .cloned()
+            .filter(|&m| {
+                board.do_move_inplace(m).unwrap();
+                let res = self.fulfils(board, plies - 1);
+                board.undo_move_inplace().unwrap();
+                res
+            })
+            .collect()
+    }
+
+    /// Walks the solution tree below a position where `moves` work.
+    fn collect_duals(&self,
+                     board: &mut Board,
+                     plies: u32,
+                     moves: &[Move],
+                     line: &mut Vec<Move>,
+                     duals: &mut Vec<Dual>) {
+        if plies <= 1 {
+            return;
+        }
+        for &m in moves {
+            board.do_move_inplace(m).unwrap();
+            line.push(m);
+            let legal = legal_moves(board);
+            if board.standard_outcome_with_moves(&legal).is_none() {
+                let next = if self.is_defending(board) {
+                    // every defence has to be answered
+                    legal.to_vec()
+                } else {
+                    let next = self.working_moves(board, &legal, plies - 1);
+                    if next.len() > 1 {
+                        duals.push(Dual {
+                            line: line.clone(),
+                            moves: next.clone(),
+                        });
+                    }
+                    next
+                };
+                self.collect_duals(board, plies - 1, &next, line, duals);
+            }
+            line.pop();
+            board.undo_move_inplace().unwrap();
+        }
+    }
+}
+
+/// Solves a problem, finding all keys and duals.
+pub fn solve(board: &Board, stipulation: Stipulation) -> Solution {
+    let solver = Solver {
+        stipulation,
+        first: board.get_side_to_move(),
+    };
+    let mut board = board.clone();
+    let plies = solver.plies();
+    if plies == 0 {
+        return Solution {
+            keys: Vec::new(),
+            duals: Vec::new(),
+        };
+    }
+    let moves = legal_moves(&mut board);
+    let keys = solver.working_moves(&mut board, &moves, plies);
+    let mut duals = Vec::new();
+    solver.collect_duals(&mut board, plies, &keys, &mut Vec::new(), &mut duals);
+    Solution { keys, duals }
+}
@@ -0,0 +1,617 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use ::board::*;
+use ::eval::*;
+use ::generator::*;
+use ::moves::*;
+use ::piece::*;
+use ::square::*;
+use ::timeman::*;
+use ::tt::*;
+
+/// Score of a checkmate at the root; mate in N plies is `MATE_SCORE - N`.
+pub const MATE_SCORE: i32 = 32000;
+/// Scores above this value (by absolute value) mean a forced mate.
+pub const MATE_BOUND: i32 = MATE_SCORE - 1000;
+const INFINITY: i32 = MATE_SCORE + 1;
+const MAX_PLY: usize = 128;
+/// Default transposition table size in megabytes.
+pub const DEFAULT_HASH_MB: usize = 16;
+
+/// Search score, either in centipawns or in moves to mate
+/// (positive if the side to move mates, negative if it gets mated).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SearchScore {
+    Centipawns(i32),
+    Mate(i32),
+}
+
+impl SearchScore {
+    pub fn from_value(value: i32) -> SearchScore {
+        if value > MATE_BOUND {
+            SearchScore::Mate((MATE_SCORE - value + 1) / 2)
+        } else if value < -MATE_BOUND {
+            SearchScore::Mate(-(MATE_SCORE + value + 1) / 2)
+        } else {
+            SearchScore::Centipawns(value)
+        }
+    }
+}
+
+/// Limits of a single search. Unset limits are not checked,
+/// a search without any limits runs until stopped.
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    /// Fixed time for this move.
+    pub movetime: Option<Duration>,
+    /// Clock state, used if `movetime` is not set.
+    pub time_control: Option<TimeControl>,
+    /// Time kept in reserve for communication delays.
+    pub move_overhead: Duration,
+}
+
+/// Progress report, sent after each completed iteration.
+#[derive(Clone, Debug)]
+pub struct SearchInfo {
+    pub depth: u32,
+    pub seldepth: u32,
+    pub score: SearchScore,
+    pub nodes: u64,
+    pub time: Duration,
+    /// Index of the reported line (1 for the best one), see `Search::set_multi_pv()`.
+    pub multipv: usize,
+    /// Permille of the transposition table in use.
+    pub hashfull: u32,
+    pub pv: Vec<Move>,
+}
+
+impl SearchInfo {
+    /// Nodes per second.
+    pub fn nps(&self) -> u64 {
+        let ms = self.time.as_secs() * 1000 + self.time.subsec_nanos() as u64 / 1_000_000;
+        self.nodes * 1000 / ms.max(1)
+    }
+}
+
+/// Final result of a search.
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    /// `None` only if there are no legal moves.
+    pub best_move: Option<Move>,
+    /// Expected reply to the best move.
+    pub ponder_move: Option<Move>,
+    pub score: SearchScore,
+    pub depth: u32,
+    pub nodes: u64,
+    pub pv: Vec<Move>,
+    /// The best lines, best first (only one unless `Search::set_multi_pv()` was called).
+    pub lines: Vec<PvLine>,
+}
+
+/// A root move with its score and principal variation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PvLine {
+    pub score: SearchScore,
+    pub pv: Vec<Move>,
+}
+
+/// Iterative deepening alpha-beta searcher. With more than one thread it runs a Lazy SMP
+/// search: helper threads search the same position at varied depths and share their results
+/// through the transposition table.
+pub struct Search {
+    params: EvalParams,
+    threads: usize,
+    multi_pv: usize,
+    tt: Arc<TranspositionTable>,
+    stop: Arc<AtomicBool>,
+    ponder: Arc<AtomicBool>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for Search {
+    fn default() -> Search {
+        Search::new()
+    }
+}
+
+impl Search {
+    pub fn new() -> Search {
+        Search::with_params(EvalParams::default())
+    }
+
+    /// Constructs a searcher using given evaluation weights.
+    pub fn with_params(params: EvalParams) -> Search {
+        Search {
+            params,
+            threads: 1,
+            multi_pv: 1,
+            tt: Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+            stop: Arc::new(AtomicBool::new(false)),
+            ponder: Arc::new(AtomicBool::new(false)),
+            clock: Arc::new(SystemClock::new()),
+        }
+    }
+
+    /// Flag that aborts the running search when set (may be set from another thread).
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    /// While this flag is set, the search ignores the time limit (pondering).
+    /// The clock starts when it is cleared.
+    pub fn ponder_handle(&self) -> Arc<AtomicBool> {
+        self.ponder.clone()
+    }
+
+    /// Replaces the clock used for time management.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Sets the number of search threads (at least one).
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn get_threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Sets the number of best root moves to report (at least one).
+    pub fn set_multi_pv(&mut self, lines: usize) {
+        self.multi_pv = lines.max(1);
+    }
+
+    pub fn get_multi_pv(&self) -> usize {
+        self.multi_pv
+    }
+
+    /// Reallocates the transposition table with the given size in megabytes.
+    pub fn set_hash_size(&mut self, mb: usize) {
+        self.tt = Arc::new(TranspositionTable::new(mb));
+    }
+
+    /// Forgets the results of previous searches (for a new game).
+    pub fn clear_hash(&mut self) {
+        self.tt.clear();
+    }
+
+    /// Searches the position, calling `on_info` after each completed iteration.
+    /// Node counts are totals of all threads.
+    pub fn run<F: FnMut(&SearchInfo)>(&mut self,
+                                      board: &Board,
+                                      limits: &SearchLimits,
+                                      mut on_info: F)
+                                      -> SearchResult {
+        let start = self.clock.now();
+        let mut board = board.clone();
+        let root_moves = legal_moves(&mut board);
+        let mut result = SearchResult {
+            best_move: root_moves.first().cloned(),
+            ponder_move: None,
+            score: SearchScore::Centipawns(0),
+            depth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+            lines: Vec::new(),
+        };
+        let variant = board.get_variant();
+        let finished = variant.special_outcome(&board)
+            .or_else(|| if root_moves.is_empty() {
+                Some(variant.no_moves_outcome(&board))
+            } else {
+                None
+            });
+        if let Some(outcome) = finished {
+            let value = outcome_value(outcome, board.get_side_to_move(), 0);
+            result.score = SearchScore::from_value(value);
+            result.lines.push(PvLine { score: result.score, pv: Vec::new() });
+            return result;
+        }
+        self.tt.new_search();
+        let shared = Shared {
+            params: &self.params,
+            tt: &self.tt,
+            stop: &self.stop,
+            helpers_stop: AtomicBool::new(false),
+            ponder: &self.ponder,
+            nodes: AtomicU64::new(0),
+            limits,
+            multi_pv: self.multi_pv.min(root_moves.len()),
+            clock: &self.clock,
+            start,
+        };
+        let mut results = Vec::new();
+        thread::scope(|scope| {
+            let helpers: Vec<_> = (1..self.threads)
+                .map(|id| {
+                    let mut worker = Worker::new(id, &shared, &board);
+                    let result = result.clone();
+                    scope.spawn(move || worker.iterate(result, |_| {}))
+                })
+                .collect();
+            let mut main = Worker::new(0, &shared, &board);
+            results.push(main.iterate(result.clone(), &mut on_info));
+            shared.helpers_stop.store(true, Ordering::SeqCst);
+            for h in helpers {
+                results.push(h.join().unwrap());
+            }
+        });
+        // the main thread's result unless a helper completed a deeper iteration
+        // (helpers only search the best line)
+        let mut best = results.remove(0);
+        for r in results {
+            if r.depth > best.depth && r.best_move.is_some() && shared.multi_pv == 1 {
+                best = r;
+            }
+        }
+        best.nodes = shared.nodes.load(Ordering::SeqCst);
+        best
+    }
+}
+
+/// State shared by all threads of one search.
+struct Shared<'a> {
+    params: &'a EvalParams,
+    tt: &'a TranspositionTable,
+    stop: &'a AtomicBool,
+    /// Set when the main thread is done.
+    helpers_stop: AtomicBool,
+    ponder: &'a AtomicBool,
+    nodes: AtomicU64,
+    limits: &'a SearchLimits,
+    multi_pv: usize,
+    clock: &'a Arc<dyn Clock>,
+    start: Duration,
+}
+
+/// Node counts are added to the shared total in batches of this size.
+const NODE_BATCH: u64 = 1024;
+
+/// One search thread. Thread 0 is the main thread: it manages time and reports progress.
+struct Worker<'a, 'b: 'a> {
+    id: usize,
+    shared: &'a Shared<'b>,
+    board: Board,
+    nodes: u64,
+    seldepth: u32,
+    time: Option<TimeManager>,
+    pondering: bool,
+    stopped: bool,
+    killers: [[Option<Move>; 2]; MAX_PLY],
+    prev_pv: Vec<Move>,
+    /// Root moves already used by better lines of the current iteration.
+    excluded: Vec<Move>,
+}
+
+impl<'a, 'b> Worker<'a, 'b> {
+    fn new(id: usize, shared: &'a Shared<'b>, board: &Board) -> Worker<'a, 'b> {
+        let limits = shared.limits;
+        let clock = shared.clock;
+        let time = if id > 0 {
+            None
+        } else {
+            match (limits.movetime, limits.time_control) {
+                (Some(t), _) => Some(TimeManager::fixed(t, limits.move_overhead, clock.clone())),
+                (None, Some(tc)) => {
+                    Some(TimeManager::new(&tc, limits.move_overhead, clock.clone()))
+                }
+                (None, None) => None,
+            }
+        };
+        Worker {
+            id,
+            shared,
+            board: board.clone(),
+            nodes: 0,
+            seldepth: 0,
+            time,
+            pondering: shared.ponder.load(Ordering::Relaxed),
+            stopped: false,
+            killers: [[None; 2]; MAX_PLY],
+            prev_pv: Vec::new(),
+            excluded: Vec::new(),
+        }
+    }
+
+    /// Runs iterative deepening until a limit is reached.
+    /// Helper threads search every other iteration one ply deeper and only the best line.
+    fn iterate<F: FnMut(&SearchInfo)>(&mut self,
+                                      mut result: SearchResult,
+                                      mut on_info: F)
+                                      -> SearchResult {
+        let limits = self.shared.limits;
+        let max_depth = limits.depth.unwrap_or(MAX_PLY as u32 - 1).min(MAX_PLY as u32 - 1);
+        let offset = (self.id % 2) as u32;
+        for iteration in 1..max_depth + 1 {
+            let depth = (iteration + offset).min(max_depth);
+            self.seldepth = 0;
+            let lines = if self.id == 0 { self.shared.multi_pv } else { 1 };
+            let mut found = Vec::new();
+            let mut board = self.board.clone();
+            self.excluded.clear();
+            while found.len() < lines {
+                let mut pv = Vec::new();
+                let value = self.negamax(&mut board, depth as i32, -INFINITY, INFINITY, 0, &mut pv);
+                if !pv.is_empty() {
+                    self.excluded.push(pv[0]);
+                    found.push((value, pv));
+                }
+                if self.stopped {
+                    break;
+                }
+            }
+            if self.stopped && (iteration > 1 || self.id > 0) || found.is_empty() {
+                break;
+            }
+            found.sort_by_key(|&(value, _)| -value);
+            let value = found[0].0;
+            let changed = result.best_move != Some(found[0].1[0]);
+            if let Some(ref mut time) = self.time {
+                time.on_iteration(changed && iteration > 1, value);
+            }
+            result.best_move = Some(found[0].1[0]);
+            result.ponder_move = found[0].1.get(1).cloned();
+            result.pv = found[0].1.clone();
+            result.score = SearchScore::from_value(value);
+            result.depth = depth;
+            result.lines = found.iter()
+                .map(|&(value, ref pv)| {
+                    PvLine {
+                        score: SearchScore::from_value(value),
+                        pv: pv.clone(),
+                    }
+                })
+                .collect();
+            if self.id == 0 {
+                let nodes = self.shared.nodes.load(Ordering::Relaxed) + self.nodes;
+                let time = self.shared.clock.now() - self.shared.start;
+                let hashfull = self.shared.tt.hashfull();
+                for (i, line) in result.lines.iter().enumerate() {
+                    on_info(&SearchInfo {
+                        depth,
+                        seldepth: self.seldepth,
+                        score: line.score,
+                        nodes,
+                        time,
+                        multipv: i + 1,
+                        hashfull,
+                        pv: line.pv.clone(),
+                    });
+                }
+            }
+            self.prev_pv = found.swap_remove(0).1;
+            if self.stopped || depth >= max_depth ||
+               value.abs() > MATE_BOUND && depth as i32 > MATE_SCORE - value.abs() {
+                break;
+            }
+            self.check_ponder();
+            if !self.pondering && self.time.as_ref().is_some_and(|t| t.should_stop()) {
+                break;
+            }
+        }
+        self.shared.nodes.fetch_add(self.nodes, Ordering::Relaxed);
+        self.nodes = 0;
+        result
+    }
+
+    /// The clock starts when pondering ends.
+    fn check_ponder(&mut self) {
+        if self.pondering && !self.shared.ponder.load(Ordering::Relaxed) {
+            self.pondering = false;
+            if let Some(ref mut time) = self.time {
+                time.restart();
+            }
+        }
+    }
+
+    fn check_limits(&mut self) {
+        let total = self.shared.nodes.fetch_add(self.nodes, Ordering::Relaxed) + self.nodes;
+        self.nodes = 0;
+        if self.shared.stop.load(Ordering::Relaxed) ||
+           self.shared.helpers_stop.load(Ordering::Relaxed) {
+            self.stopped = true;
+        }
+        if self.shared.limits.nodes.is_some_and(|n| total >= n) {
+            self.stopped = true;
+        }
+        self.check_ponder();
+        if !self.pondering && self.time.as_ref().is_some_and(|t| t.should_abort()) {
+            self.stopped = true;
+        }
+    }
+
+    fn count_node(&mut self) {
+        self.nodes += 1;
+        if self.nodes >= NODE_BATCH {
+            self.check_limits();
+        }
+    }
+
+    fn negamax(&mut self,
+               board: &mut Board,
+               mut depth: i32,
+               mut alpha: i32,
+               beta: i32,
+               ply: usize,
+               pv: &mut Vec<Move>)
+               -> i32 {
+        self.count_node();
+        if self.stopped && ply > 0 {
+            return 0;
+        }
+        if ply > 0 {
+            if let Some(outcome) = board.get_variant().special_outcome(board) {
+                return outcome_value(outcome, board.get_side_to_move(), ply);
+            }
+            if board.get_halfmove_count() >= 100 || board.is_repetition() {
+                return 0;
+            }
+        }
+        let in_check = board.is_in_check();
+        if in_check {
+            depth += 1;
+        }
+        if depth <= 0 || ply >= MAX_PLY - 1 {
+            return self.quiescence(board, alpha, beta, ply);
+        }
+        let key = board.get_hash();
+        let entry = self.shared.tt.probe(key, ply);
+        if let Some(e) = entry {
+            if ply > 0 && e.depth >= depth &&
+               match e.bound {
+                Bound::Exact => true,
+                Bound::Lower => e.score >= beta,
+                Bound::Upper => e.score <= alpha,
+            } {
+                return e.score;
+            }
+        }
+        let pv_move = if self.prev_pv.len() > ply &&
+                         board.get_move_list()[board.get_move_list().len() - ply..] ==
+                         self.prev_pv[..ply] {
+            Some(self.prev_pv[ply])
+        } else {
+            None
+        };
+        // the move of the previous principal variation stands in for a missing table move
+        let hash_move = entry.and_then(|e| e.get_best_move(board)).or(pv_move);
+        let mut moves = MoveGen::new(MoveFilter::All)
+            .with_hash_move(hash_move)
+            .with_killers(self.killers[ply]);
+        let variant = board.get_variant();
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        let mut legal_count = 0;
+        let mut child_pv = Vec::new();
+        while let Some(m) = moves.next_move(board) {
+            if ply == 0 && self.excluded.contains(&m) {
+                // searched by a better line already
+                legal_count += 1;
+                continue;
+            }
+            board.do_move_inplace(m).unwrap();
+            if !variant.is_legal(board, m) {
+                board.undo_move_inplace().unwrap();
+                continue;
+            }
+            legal_count += 1;
+            child_pv.clear();
+            let value = -self.negamax(board, depth - 1, -beta, -alpha, ply + 1, &mut child_pv);
+            board.undo_move_inplace().unwrap();
+            if self.stopped && ply > 0 {
+                return 0;
+            }
+            if value > best {
+                best = value;
+                if value > alpha {
+                    alpha = value;
+                    best_move = Some(m);
+                    pv.clear();
+                    pv.push(m);
+                    pv.extend_from_slice(&child_pv);
+                    if alpha >= beta {
+                        if m.get_captured_piece().is_none() && self.killers[ply][0] != Some(m) {
+                            self.killers[ply][1] = self.killers[ply][0];
+                            self.killers[ply][0] = Some(m);
+                        }
+                        break;
+                    }
+                }
+            }
+            if self.stopped {
+                // only the moves searched so far count at the root
+                break;
+            }
+        }
+        if legal_count == 0 {
+            let outcome = variant.no_moves_outcome(board);
+            return outcome_value(outcome, board.get_side_to_move(), ply);
+        }
+        // results at the root without the excluded moves don't describe the position
+        if !self.stopped && (ply > 0 || self.excluded.is_empty()) {
+            let bound = if best >= beta {
+                Bound::Lower
+            } else if best > original_alpha {
+                Bound::Exact
+            } else {
+                Bound::Upper
+            };
+            self.shared.tt.store(key, ply, best_move, best, depth, bound);
+        }
+        best
+    }
+
+    fn quiescence(&mut self, board: &mut Board, mut alpha: i32, beta: i32, ply: usize) -> i32 {
+        self.count_node();
+        self.seldepth = self.seldepth.max(ply as u32);
+        if self.stopped {
+            return 0;
+        }
+        if let Some(outcome) = board.get_variant().special_outcome(board) {
+            return outcome_value(outcome, board.get_side_to_move(), ply);
+        }
+        let stand_pat = evaluate(board, self.shared.params);
+        if stand_pat >= beta || ply >= MAX_PLY - 1 {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+        // captures not losing material, then queen promotions not losing material
+        let variant = board.get_variant();
+        let mut captures = MoveGen::new(MoveFilter::Captures);
+        let mut promotions: Option<MoveGen> = None;
+        loop {
+            let m = match promotions {
+                None => {
+                    match captures.next_move(board) {
+                        Some(m) if captures.get_stage() == Stage::GoodCaptures => m,
+                        _ => {
+                            promotions = Some(MoveGen::new(MoveFilter::Promotions));
+                            continue;
+                        }
+                    }
+                }
+                Some(ref mut gen) => {
+                    match gen.next_move(board) {
+                        Some(m) => m,
+                        None => break,
+                    }
+                }
+            };
+            if m.get_captured_piece().is_none() &&
+               (m.get_promoted_to() != Some(PieceKind::Queen) || !board.see_ge(m, 0)) {
+                continue;
+            }
+            board.do_move_inplace(m).unwrap();
+            if !variant.is_legal(board, m) {
+                board.undo_move_inplace().unwrap();
+                continue;
+            }
+            let value = -self.quiescence(board, -beta, -alpha, ply + 1);
+            board.undo_move_inplace().unwrap();
+            if self.stopped {
+                return 0;
+            }
+            if value >= beta {
+                return value;
+            }
+            alpha = alpha.max(value);
+        }
+        alpha
+    }
+}
+
+/// Score of a finished game for the side to move, mates `ply` plies from the root.
+fn outcome_value(outcome: Outcome, side_to_move: Color, ply: usize) -> i32 {
+    match outcome.winner() {
+        Some(c) if c == side_to_move => MATE_SCORE - ply as i32,
+        Some(_) => -MATE_SCORE + ply as i32,
+        None => 0,
+    }
+}
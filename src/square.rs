@@ -6,6 +6,16 @@ pub enum Color {
     Black,
 }
 
+impl Color {
+    /// Returns the other color.
+    pub fn opposite(&self) -> Color {
+        match *self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub enum Rank {
     First,
@@ -16,6 +26,33 @@ pub enum Rank {
     Sixth,
     Seventh,
     Eighth,
+    Ninth,
+    Tenth,
+}
+
+impl Rank {
+    /// Returns rank number `num` (0 for the first rank), treated modulo `MAX_RANKS`.
+    pub fn from_int(num: u32) -> Rank {
+        match num % MAX_RANKS as u32 {
+            0 => Rank::First,
+            1 => Rank::Second,
+            2 => Rank::Third,
+            3 => Rank::Fourth,
+            4 => Rank::Fifth,
+            5 => Rank::Sixth,
+            6 => Rank::Seventh,
+            7 => Rank::Eighth,
+            8 => Rank::Ninth,
+            9 => Rank::Tenth,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the rank the pieces of a given color start on (on the standard board,
+    /// see `Geometry::back_rank()` for others).
+    pub fn back_rank(color: Color) -> Rank {
+        Geometry::STANDARD.back_rank(color)
+    }
 }
 
 impl fmt::Display for Rank {
@@ -34,6 +71,27 @@ pub enum File {
     F,
     G,
     H,
+    I,
+    J,
+}
+
+impl File {
+    /// Returns file number `num` (0 for the A file), treated modulo `MAX_FILES`.
+    pub fn from_int(num: u32) -> File {
+        match num % MAX_FILES as u32 {
+            0 => File::A,
+            1 => File::B,
+            2 => File::C,
+            3 => File::D,
+            4 => File::E,
+            5 => File::F,
+            6 => File::G,
+            7 => File::H,
+            8 => File::I,
+            9 => File::J,
+            _ => unreachable!(),
+        }
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -48,200 +106,255 @@ pub enum Direction {
     DownRight,
 }
 
+impl Direction {
+    /// Returns the change of the file and the rank of a step in this direction.
+    pub fn offset(&self) -> (i32, i32) {
+        match *self {
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, 1),
+            Direction::UpRight => (1, 1),
+            Direction::DownLeft => (-1, -1),
+            Direction::DownRight => (1, -1),
+        }
+    }
+}
+
 impl fmt::Display for File {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", format!("{:?}", self).to_lowercase())
     }
 }
 
+/// The largest supported board is `MAX_FILES` by `MAX_RANKS`.
+pub const MAX_FILES: usize = 10;
+pub const MAX_RANKS: usize = 10;
+/// Size of arrays indexed by `Square::as_index()`, large enough for any geometry.
+pub const SQUARE_COUNT: usize = MAX_FILES * MAX_RANKS;
+
+/// Dimensions of a board: files from A and ranks from the first one, White at the bottom.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Geometry {
+    files: u8,
+    ranks: u8,
+}
+
+impl Geometry {
+    /// The standard 8x8 board.
+    pub const STANDARD: Geometry = Geometry { files: 8, ranks: 8 };
+    /// The 10x8 board of Capablanca chess and its relatives.
+    pub const CAPABLANCA: Geometry = Geometry { files: 10, ranks: 8 };
+    /// The 5x5 board of Gardner minichess.
+    pub const GARDNER: Geometry = Geometry { files: 5, ranks: 5 };
+    /// The 6x6 board of Los Alamos chess.
+    pub const LOS_ALAMOS: Geometry = Geometry { files: 6, ranks: 6 };
+
+    /// Returns a geometry of the given size or `None` if it is not supported.
+    pub fn new(files: u8, ranks: u8) -> Option<Geometry> {
+        if (1..=MAX_FILES as u8).contains(&files) && (1..=MAX_RANKS as u8).contains(&ranks) {
+            Some(Geometry { files, ranks })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_files(&self) -> u8 {
+        self.files
+    }
+
+    pub fn get_ranks(&self) -> u8 {
+        self.ranks
+    }
+
+    pub fn get_last_file(&self) -> File {
+        File::from_int(self.files as u32 - 1)
+    }
+
+    pub fn get_last_rank(&self) -> Rank {
+        Rank::from_int(self.ranks as u32 - 1)
+    }
+
+    /// Returns the rank the pieces of a given color start on.
+    pub fn back_rank(&self, color: Color) -> Rank {
+        self.relative_rank(color, 0)
+    }
+
+    /// Returns the rank `n` steps forward from the back rank of `color`.
+    pub fn relative_rank(&self, color: Color, n: u8) -> Rank {
+        match color {
+            Color::White => Rank::from_int(n as u32),
+            Color::Black => Rank::from_int((self.ranks - 1).saturating_sub(n) as u32),
+        }
+    }
+
+    /// Returns `true` if the square is on the board.
+    pub fn contains(&self, s: Square) -> bool {
+        (s.get_file() as u8) < self.files && (s.get_rank() as u8) < self.ranks
+    }
+
+    /// Returns the number of squares.
+    pub fn square_count(&self) -> usize {
+        self.files as usize * self.ranks as usize
+    }
+
+    /// Iterates over the squares of the board, rank by rank from a1.
+    pub fn squares(&self) -> impl Iterator<Item = Square> {
+        let files = self.files as usize;
+        (0..self.ranks as usize * MAX_FILES)
+            .filter(move |i| i % MAX_FILES < files)
+            .map(|i| Square(i as u8))
+    }
+}
+
+/// A square of a board of any geometry (see `Geometry`). The methods without a geometry
+/// argument, like `get_up()`, refer to the standard board.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Square(u8);
 
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [(1, 2), (-1, 2), (1, -2), (-1, -2), (2, 1), (-2, 1),
+                                         (2, -1), (-2, -1)];
+
 impl Square {
+    /// Returns square number `num` of the standard board (a1 is 0 and h8 is 63),
+    /// treated modulo 64.
     pub fn from_int(num: u32) -> Square {
-        Square((num % 64) as u8)
+        let num = num % 64;
+        Square::from_file_and_rank(File::from_int(num % 8), Rank::from_int(num / 8))
+    }
+
+    /// Returns the square with a given `as_index()` (not the 0 to 63 number of `from_int()`).
+    pub fn from_index(index: usize) -> Square {
+        Square((index % SQUARE_COUNT) as u8)
     }
 
     pub fn from_file_and_rank(file: File, rank: Rank) -> Square {
-        Square(file as u8 + 8 * rank as u8)
+        Square(file as u8 + MAX_FILES as u8 * rank as u8)
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(string: &str) -> Option<Square> {
         let mut chars = string.chars();
         let file = match chars.next() {
-            Some('a') => File::A,
-            Some('b') => File::B,
-            Some('c') => File::C,
-            Some('d') => File::D,
-            Some('e') => File::E,
-            Some('f') => File::F,
-            Some('g') => File::G,
-            Some('h') => File::H,
+            Some(c @ 'a'..='j') => File::from_int(c as u32 - 'a' as u32),
             _ => return None,
         };
-        let rank = match chars.next() {
-            Some('1') => Rank::First,
-            Some('2') => Rank::Second,
-            Some('3') => Rank::Third,
-            Some('4') => Rank::Fourth,
-            Some('5') => Rank::Fifth,
-            Some('6') => Rank::Sixth,
-            Some('7') => Rank::Seventh,
-            Some('8') => Rank::Eighth,
+        let digits = chars.as_str();
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let rank = match digits.parse::<u32>() {
+            Ok(n) if (1..=MAX_RANKS as u32).contains(&n) && !chars.as_str().starts_with('0') => {
+                Rank::from_int(n - 1)
+            }
             _ => return None,
         };
-        match chars.next() {
-            None => return Some(Square::from_file_and_rank(file, rank)),
-            _ => return None,
-        }
+        Some(Square::from_file_and_rank(file, rank))
     }
 
     pub fn get_file(&self) -> File {
-        match self.0 % 8 {
-            0 => File::A,
-            1 => File::B,
-            2 => File::C,
-            3 => File::D,
-            4 => File::E,
-            5 => File::F,
-            6 => File::G,
-            7 => File::H,
-            _ => unreachable!(),
-        }
+        File::from_int(self.0 as u32 % MAX_FILES as u32)
     }
     pub fn get_rank(&self) -> Rank {
-        match self.0 / 8 {
-            0 => Rank::First,
-            1 => Rank::Second,
-            2 => Rank::Third,
-            3 => Rank::Fourth,
-            4 => Rank::Fifth,
-            5 => Rank::Sixth,
-            6 => Rank::Seventh,
-            7 => Rank::Eighth,
-            _ => unreachable!(),
+        Rank::from_int(self.0 as u32 / MAX_FILES as u32)
+    }
+
+    /// Returns the square `files` files to the right and `ranks` ranks up,
+    /// or `None` if it is off the board.
+    pub fn get_by_offset(&self, files: i32, ranks: i32, geometry: Geometry) -> Option<Square> {
+        let f = (self.0 as usize % MAX_FILES) as i32 + files;
+        let r = (self.0 as usize / MAX_FILES) as i32 + ranks;
+        if f < 0 || r < 0 || f >= geometry.files as i32 || r >= geometry.ranks as i32 {
+            return None;
         }
+        Some(Square((f + MAX_FILES as i32 * r) as u8))
     }
-    pub fn get_up(&self) -> Option<Square> {
-        if self.get_rank() != Rank::Eighth {
-            Some(Square(self.0 + 8))
-        } else {
-            None
+
+    pub fn get_by_dir_in(&self, dir: Direction, geometry: Geometry) -> Option<Square> {
+        let (files, ranks) = dir.offset();
+        self.get_by_offset(files, ranks, geometry)
+    }
+
+    pub fn get_forward_in(&self, color: Color, geometry: Geometry) -> Option<Square> {
+        match color {
+            Color::White => self.get_by_offset(0, 1, geometry),
+            Color::Black => self.get_by_offset(0, -1, geometry),
         }
     }
 
+    pub fn get_back_in(&self, color: Color, geometry: Geometry) -> Option<Square> {
+        self.get_forward_in(color.opposite(), geometry)
+    }
+
+    pub fn get_knight_moves_in(&self, geometry: Geometry) -> Vec<Square> {
+        self.iter_knight_moves_in(geometry).collect()
+    }
+
+    /// Like `get_knight_moves_in()`, without allocating.
+    pub fn iter_knight_moves_in(&self, geometry: Geometry) -> impl Iterator<Item = Square> {
+        let s = *self;
+        KNIGHT_OFFSETS.iter().filter_map(move |&(f, r)| s.get_by_offset(f, r, geometry))
+    }
+
+    pub fn get_up(&self) -> Option<Square> {
+        self.get_by_dir(Direction::Up)
+    }
+
     pub fn get_down(&self) -> Option<Square> {
-        if self.get_rank() != Rank::First {
-            Some(Square(self.0 - 8))
-        } else {
-            None
-        }
+        self.get_by_dir(Direction::Down)
     }
     pub fn get_left(&self) -> Option<Square> {
-        if self.get_file() != File::A {
-            Some(Square(self.0 - 1))
-        } else {
-            None
-        }
+        self.get_by_dir(Direction::Left)
     }
 
     pub fn get_right(&self) -> Option<Square> {
-        if self.get_file() != File::H {
-            Some(Square(self.0 + 1))
-        } else {
-            None
-        }
+        self.get_by_dir(Direction::Right)
     }
     pub fn get_up_left(&self) -> Option<Square> {
-        if self.get_rank() != Rank::Eighth && self.get_file() != File::A {
-            Some(Square(self.0 + 7))
-        } else {
-            None
-        }
+        self.get_by_dir(Direction::UpLeft)
     }
 
     pub fn get_up_right(&self) -> Option<Square> {
-        if self.get_rank() != Rank::Eighth && self.get_file() != File::H {
-            Some(Square(self.0 + 9))
-        } else {
-            None
-        }
+        self.get_by_dir(Direction::UpRight)
     }
     pub fn get_down_left(&self) -> Option<Square> {
-        if self.get_rank() != Rank::First && self.get_file() != File::A {
-            Some(Square(self.0 - 9))
-        } else {
-            None
-        }
+        self.get_by_dir(Direction::DownLeft)
     }
 
     pub fn get_down_right(&self) -> Option<Square> {
-        if self.get_rank() != Rank::First && self.get_file() != File::H {
-            Some(Square(self.0 - 7))
-        } else {
-            None
-        }
+        self.get_by_dir(Direction::DownRight)
     }
 
     pub fn get_forward(&self, color: Color) -> Option<Square> {
-        match color {
-            Color::White => self.get_up(),
-            Color::Black => self.get_down(),
-        }
+        self.get_forward_in(color, Geometry::STANDARD)
     }
 
     pub fn get_back(&self, color: Color) -> Option<Square> {
-        match color {
-            Color::White => self.get_down(),
-            Color::Black => self.get_up(),
-        }
+        self.get_back_in(color, Geometry::STANDARD)
     }
 
     pub fn get_knight_moves(&self) -> Vec<Square> {
-        let mut res = Vec::new();
-        if self.get_rank() <= Rank::Sixth && self.get_file() != File::H {
-            res.push(Square(self.0 + 17));
-        }
-        if self.get_rank() <= Rank::Sixth && self.get_file() != File::A {
-            res.push(Square(self.0 + 15));
-        }
-        if self.get_rank() >= Rank::Third && self.get_file() != File::H {
-            res.push(Square(self.0 - 15));
-        }
-        if self.get_rank() >= Rank::Third && self.get_file() != File::A {
-            res.push(Square(self.0 - 17));
-        }
-        if self.get_rank() != Rank::Eighth && self.get_file() <= File::F {
-            res.push(Square(self.0 + 10));
-        }
-        if self.get_rank() != Rank::Eighth && self.get_file() >= File::C {
-            res.push(Square(self.0 + 6));
-        }
-        if self.get_rank() != Rank::First && self.get_file() <= File::F {
-            res.push(Square(self.0 - 6));
-        }
-        if self.get_rank() != Rank::First && self.get_file() >= File::C {
-            res.push(Square(self.0 - 10));
-        }
-        res
+        self.get_knight_moves_in(Geometry::STANDARD)
     }
 
     pub fn get_by_dir(&self, dir: Direction) -> Option<Square> {
-        match dir {
-            Direction::Up => self.get_up(),
-            Direction::Down => self.get_down(),
-            Direction::Left => self.get_left(),
-            Direction::Right => self.get_right(),
-            Direction::UpLeft => self.get_up_left(),
-            Direction::UpRight => self.get_up_right(),
-            Direction::DownLeft => self.get_down_left(),
-            Direction::DownRight => self.get_down_right(),
-        }
+        self.get_by_dir_in(dir, Geometry::STANDARD)
     }
 
+    /// Index into arrays of `SQUARE_COUNT` elements: `file + MAX_FILES * rank`, so a1 is 0,
+    /// h1 is 7 and a2 is 10. This covers every supported board size; before boards could be
+    /// larger than 8x8 it was `file + 8 * rank` (0 to 63), which `to_int()` still returns.
     pub fn as_index(&self) -> usize {
         self.0 as usize
     }
+
+    /// Returns the number of the square on the standard board (a1 is 0 and h8 is 63,
+    /// see `from_int()`), or `None` outside of it.
+    pub fn to_int(&self) -> Option<u32> {
+        let (file, rank) = (self.get_file() as u32, self.get_rank() as u32);
+        if file < 8 && rank < 8 { Some(file + 8 * rank) } else { None }
+    }
 }
 
 impl fmt::Display for Square {
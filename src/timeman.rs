@@ -0,0 +1,191 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Source of time for the time manager, so that it can be tested deterministically.
+pub trait Clock: Send + Sync {
+    /// Time passed since some fixed moment.
+    fn now(&self) -> Duration;
+}
+
+/// Wall clock time.
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock { start: Instant::now() }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Clock that only moves when told to (for tests and simulations).
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    micros: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, d: Duration) {
+        self.micros.fetch_add(d.as_secs() * 1_000_000 + d.subsec_micros() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_micros(self.micros.load(Ordering::SeqCst))
+    }
+}
+
+/// How the clock is compensated after each move.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Increment {
+    #[default]
+    None,
+    /// The increment is added after every move, whether used or not.
+    Fischer(Duration),
+    /// The time used is given back after the move, up to the given amount.
+    Bronstein(Duration),
+    /// The clock starts running only after the delay has passed (simple or US delay).
+    Delay(Duration),
+}
+
+/// The state of the engine's clock when a search starts.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TimeControl {
+    /// Time left on the clock.
+    pub remaining: Duration,
+    pub increment: Increment,
+    /// Moves left until the next time control (classical controls like 40 moves in 2 hours),
+    /// `None` for sudden death.
+    pub moves_to_go: Option<u32>,
+}
+
+/// Moves the rest of a sudden death game is assumed to take.
+const SUDDEN_DEATH_MOVES: u32 = 30;
+/// The soft limit may be stretched up to this many times (in percent).
+const MAX_SCALE: u32 = 250;
+
+/// Turns the clock state into time limits for one search.
+/// The soft limit is checked between iterations (no new iteration is started after it),
+/// the hard limit aborts the search. The soft limit grows when the best move changes or
+/// the score drops, but never beyond the hard limit.
+#[derive(Clone)]
+pub struct TimeManager {
+    clock: Arc<dyn Clock>,
+    start: Duration,
+    soft: Duration,
+    hard: Duration,
+    /// Current soft limit scale in percent.
+    scale: u32,
+    last_score: Option<i32>,
+}
+
+impl TimeManager {
+    /// Limits for a tournament time control. `move_overhead` is kept in reserve
+    /// for communication delays.
+    pub fn new(tc: &TimeControl, move_overhead: Duration, clock: Arc<dyn Clock>) -> TimeManager {
+        let usable = tc.remaining.checked_sub(move_overhead).unwrap_or_default();
+        let moves = tc.moves_to_go.unwrap_or(SUDDEN_DEATH_MOVES).clamp(1, 50);
+        // time gained with this move and time that isn't charged at all
+        let zero = Duration::from_secs(0);
+        let (bonus, free) = match tc.increment {
+            Increment::None => (zero, zero),
+            Increment::Fischer(inc) => (inc * 3 / 4, zero),
+            Increment::Bronstein(d) => (d, zero),
+            Increment::Delay(d) => (d, d),
+        };
+        let (soft_cap, hard_cap) = if moves == 1 {
+            // the last move before the control may use nearly everything
+            (usable * 3 / 4, usable * 9 / 10)
+        } else {
+            (usable / 4, usable / 2)
+        };
+        let soft = (usable / moves + bonus).min(soft_cap + free);
+        let hard = (soft * 4).min(hard_cap + free).max(soft);
+        TimeManager::with_limits(soft, hard, clock)
+    }
+
+    /// Limits for a fixed time per move.
+    pub fn fixed(movetime: Duration, move_overhead: Duration, clock: Arc<dyn Clock>) -> TimeManager {
+        let t = movetime.checked_sub(move_overhead).unwrap_or(movetime / 2);
+        TimeManager::with_limits(t, t, clock)
+    }
+
+    fn with_limits(soft: Duration, hard: Duration, clock: Arc<dyn Clock>) -> TimeManager {
+        TimeManager {
+            start: clock.now(),
+            clock,
+            soft,
+            hard,
+            scale: 100,
+            last_score: None,
+        }
+    }
+
+    /// Restarts the clock (for example on `ponderhit`).
+    pub fn restart(&mut self) {
+        self.start = self.clock.now();
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.clock.now() - self.start
+    }
+
+    /// Soft limit, taking the extensions into account.
+    pub fn soft_limit(&self) -> Duration {
+        (self.soft * self.scale / 100).min(self.hard)
+    }
+
+    pub fn hard_limit(&self) -> Duration {
+        self.hard
+    }
+
+    /// Reports a finished iteration: whether the best move differs from the previous iteration
+    /// and the new score (from the side to move's point of view).
+    pub fn on_iteration(&mut self, best_move_changed: bool, score: i32) {
+        let mut scale = self.scale;
+        if best_move_changed {
+            scale = scale * 3 / 2;
+        } else {
+            // a stable best move lets the extension wear off
+            scale = (scale * 9 / 10).max(100);
+        }
+        if let Some(last) = self.last_score {
+            let drop = last - score;
+            if drop >= 100 {
+                scale = scale.max(200);
+            } else if drop >= 30 {
+                scale = scale.max(150);
+            }
+        }
+        self.scale = scale.min(MAX_SCALE);
+        self.last_score = Some(score);
+    }
+
+    /// Returns `true` if a new iteration shouldn't be started.
+    pub fn should_stop(&self) -> bool {
+        self.elapsed() >= self.soft_limit()
+    }
+
+    /// Returns `true` if the search must be aborted immediately.
+    pub fn should_abort(&self) -> bool {
+        self.elapsed() >= self.hard
+    }
+}
@@ -0,0 +1,195 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use ::board::*;
+use ::moves::*;
+use ::piece::*;
+use ::search::MATE_BOUND;
+use ::square::*;
+
+/// Kind of a stored score relative to the true value.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Bound {
+    Exact,
+    /// The true value is at least the score (a beta cutoff).
+    Lower,
+    /// The true value is at most the score (no move raised alpha).
+    Upper,
+}
+
+/// Data found in the table.
+#[derive(Copy, Clone, Debug)]
+pub struct TtEntry {
+    best_move: u32,
+    pub score: i32,
+    pub depth: i32,
+    pub bound: Bound,
+}
+
+impl TtEntry {
+    /// Returns the best move stored for the position if it is pseudo-legal on the board
+    /// (see `pack_move()` for what is stored).
+    pub fn get_best_move(&self, board: &Board) -> Option<Move> {
+        if self.best_move == 0 {
+            return None;
+        }
+        let from = (self.best_move & 127) as usize;
+        let to = (self.best_move >> 7 & 127) as usize;
+        let kind = (self.best_move >> 14 & 15) as usize;
+        if from >= SQUARE_COUNT || to >= SQUARE_COUNT || kind > KIND_COUNT {
+            return None;
+        }
+        let (from, to) = (Square::from_index(from), Square::from_index(to));
+        let kind = if kind == 0 { None } else { Some(ALL_KINDS[kind - 1]) };
+        let color = board.get_side_to_move();
+        let m = if from == to {
+            Move::new_drop(Piece::new(kind?, color), to)
+        } else {
+            let p = board.get_piece(from)?;
+            let castling = self.best_move >> 18 & 1 != 0;
+            let en_passant = p.get_kind() == PieceKind::Pawn && from.get_file() != to.get_file() &&
+                             board.get_en_passant_square() == Some(to);
+            let captured = if en_passant {
+                Some(Piece::new(PieceKind::Pawn, color.opposite()))
+            } else if castling {
+                None
+            } else {
+                board.get_piece(to)
+            };
+            Move::new(p, from, to, captured, kind, en_passant, castling)
+        };
+        if board.is_pseudo_legal(m) { Some(m) } else { None }
+    }
+}
+
+/// Shared hash table of search results. It is lock-free: each entry holds the key xor-ed
+/// with the data, so entries torn by concurrent writes are simply not found.
+pub struct TranspositionTable {
+    entries: Vec<[AtomicU64; 2]>,
+    generation: AtomicU8,
+}
+
+const ENTRY_SIZE: usize = 16;
+
+impl TranspositionTable {
+    /// Allocates a table of about `mb` megabytes.
+    pub fn new(mb: usize) -> TranspositionTable {
+        let count = (mb.max(1) << 20) / ENTRY_SIZE;
+        TranspositionTable {
+            entries: (0..count).map(|_| [AtomicU64::new(0), AtomicU64::new(0)]).collect(),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    fn slot(&self, key: u64) -> &[AtomicU64; 2] {
+        &self.entries[((key as u128 * self.entries.len() as u128) >> 64) as usize]
+    }
+
+    /// Removes all entries.
+    pub fn clear(&self) {
+        for e in &self.entries {
+            e[0].store(0, Ordering::Relaxed);
+            e[1].store(0, Ordering::Relaxed);
+        }
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    /// Marks the start of a new search, entries of older searches are replaced first.
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Looks up the position, mate scores are converted to be relative to `ply`.
+    pub fn probe(&self, key: u64, ply: usize) -> Option<TtEntry> {
+        let slot = self.slot(key);
+        let data = slot[1].load(Ordering::Relaxed);
+        if data == 0 || slot[0].load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+        let mut score = (data >> 20) as u16 as i16 as i32;
+        if score > MATE_BOUND {
+            score -= ply as i32;
+        } else if score < -MATE_BOUND {
+            score += ply as i32;
+        }
+        Some(TtEntry {
+            best_move: data as u32 & MOVE_MASK,
+            score,
+            depth: (data >> 36) as u8 as i32,
+            bound: match (data >> 44) & 3 {
+                0 => Bound::Exact,
+                1 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+        })
+    }
+
+    /// Stores a search result. Deeper results and results of the current search are kept
+    /// in favour of others.
+    pub fn store(&self,
+                 key: u64,
+                 ply: usize,
+                 best_move: Option<Move>,
+                 score: i32,
+                 depth: i32,
+                 bound: Bound) {
+        let slot = self.slot(key);
+        let generation = self.generation.load(Ordering::Relaxed);
+        let old = slot[1].load(Ordering::Relaxed);
+        let old_key = slot[0].load(Ordering::Relaxed) ^ old;
+        let old_depth = (old >> 36) as u8 as i32;
+        let old_generation = (old >> 48) as u8;
+        if old != 0 && old_generation == generation && old_key != key && old_depth > depth {
+            return;
+        }
+        let mut best_move = best_move.map_or(0, pack_move);
+        if best_move == 0 && old_key == key {
+            best_move = old as u32 & MOVE_MASK;
+        }
+        let score = if score > MATE_BOUND {
+            score + ply as i32
+        } else if score < -MATE_BOUND {
+            score - ply as i32
+        } else {
+            score
+        };
+        let bound = match bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        let data = best_move as u64 | (score as i16 as u16 as u64) << 20 |
+                   (depth.clamp(0, 255) as u64) << 36 | bound << 44 |
+                   (generation as u64) << 48 | 1 << 56;
+        slot[0].store(key ^ data, Ordering::Relaxed);
+        slot[1].store(data, Ordering::Relaxed);
+    }
+
+    /// Permille of the table used by the current search (estimated from a sample).
+    pub fn hashfull(&self) -> u32 {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let sample = self.entries.len().min(1000);
+        let used = self.entries[..sample]
+            .iter()
+            .filter(|e| {
+                let data = e[1].load(Ordering::Relaxed);
+                data != 0 && (data >> 48) as u8 == generation
+            })
+            .count();
+        (used * 1000 / sample) as u32
+    }
+}
+
+/// Bits of the data holding the move (see `pack_move()`).
+const MOVE_MASK: u32 = (1 << 20) - 1;
+
+/// Packs the squares (7 bits each), the promotion and the castling flag into 20 bits.
+/// Drops have equal squares and the dropped piece in place of the promotion.
+fn pack_move(m: Move) -> u32 {
+    if m.is_drop() {
+        let to = m.get_square_to().as_index() as u32;
+        return to | to << 7 | (1 + m.get_moving_piece().get_kind() as u32) << 14;
+    }
+    let promotion = m.get_promoted_to().map_or(0, |k| 1 + k as u32);
+    m.get_square_from().as_index() as u32 | (m.get_square_to().as_index() as u32) << 7 |
+    promotion << 14 | (m.is_castling() as u32) << 18
+}
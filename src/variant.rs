@@ -0,0 +1,1120 @@
+use ::betza::*;
+use ::board::*;
+use ::error::*;
+use ::generator::*;
+use ::moves::*;
+use ::piece::*;
+use ::square::*;
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const CAPABLANCA_FEN: &str = "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1";
+const GOTHIC_FEN: &str = "rnbqckabnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNBQCKABNR w KQkq - 0 1";
+
+/// Rules of a chess variant. Every `Board` refers to its variant and dispatches move
+/// generation, legality checks, game end detection and FEN handling through it.
+/// The default methods implement standard chess.
+pub trait Variant: Sync {
+    /// Name used in the UCI `UCI_Variant` option.
+    fn name(&self) -> &'static str;
+
+    /// Value of the PGN `Variant` tag.
+    fn pgn_name(&self) -> &'static str;
+
+    /// Name used by the XBoard `variant` command.
+    fn xboard_name(&self) -> &'static str {
+        self.name()
+    }
+
+    /// Returns `true` if castling rooks may start on any file, so that castling is written
+    /// as the king capturing its own rook in UCI notation.
+    fn is_chess960(&self) -> bool {
+        false
+    }
+
+    /// Returns the dimensions of the board.
+    fn geometry(&self) -> Geometry {
+        Geometry::STANDARD
+    }
+
+    /// Returns the starting position. It takes `&'static self` since the board refers to
+    /// the variant.
+    fn start_position(&'static self) -> Board;
+
+    /// Returns `true` if captured pieces go into the capturer's pocket.
+    fn captures_to_pocket(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if captures explode the capturing piece and the pieces around.
+    fn explodes(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if checks are counted (see `Board::get_checks_remaining()`).
+    fn counts_checks(&self) -> bool {
+        false
+    }
+
+    /// Adds the pseudo-legal moves of the given kinds of the side to move to `res`.
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        standard_pseudo_legal_moves(board, kinds, res);
+    }
+
+    /// Returns `true` if `m` is one of the moves `pseudo_legal_moves()` would generate
+    /// (see `Board::is_pseudo_legal()`), so variants generating other moves override both.
+    fn is_pseudo_legal(&self, board: &Board, m: Move) -> bool {
+        standard_is_pseudo_legal(board, m, &STANDARD_PROMOTIONS)
+    }
+
+    /// Checks a pseudo-legal move given the position after it.
+    fn is_legal(&self, after: &Board, m: Move) -> bool {
+        let color = m.get_moving_piece().get_color();
+        !after.has_king(color) ||
+        !after.is_square_attacked(after.get_king_square(color), color.opposite())
+    }
+
+    /// Restricts the legal moves (for example to compulsory captures). The list may hold
+    /// only the captures or only the quiet moves of the position, see `MoveGen`.
+    fn filter_legal_moves(&self, _board: &Board, _moves: &mut MoveList) {}
+
+    /// Returns `true` if the square `s` is attacked by any piece of color `by`.
+    fn is_square_attacked(&self, board: &Board, s: Square, by: Color) -> bool {
+        board.least_valuable_attacker_kind(s, by).is_some()
+    }
+
+    /// Returns `true` if a king of `color` on the square `s` would be in check,
+    /// used for the squares the king passes when castling.
+    fn is_king_square_attacked(&self, board: &Board, s: Square, color: Color) -> bool {
+        board.is_square_attacked(s, color.opposite())
+    }
+
+    /// Returns `true` if the king of the side to move is in check.
+    fn is_in_check(&self, board: &Board) -> bool {
+        let color = board.get_side_to_move();
+        board.has_king(color) &&
+        board.is_square_attacked(board.get_king_square(color), color.opposite())
+    }
+
+    /// Returns the outcome if the game is over.
+    fn outcome(&self, board: &Board) -> Option<Outcome> {
+        board.standard_outcome()
+    }
+
+    /// Game end by the variant's own rules, regardless of the legal moves.
+    /// It is checked first, by the search as well.
+    fn special_outcome(&self, _board: &Board) -> Option<Outcome> {
+        None
+    }
+
+    /// Returns the outcome when the side to move has no legal moves.
+    fn no_moves_outcome(&self, board: &Board) -> Outcome {
+        if board.is_in_check() {
+            Outcome::Checkmate(board.get_side_to_move().opposite())
+        } else {
+            Outcome::Stalemate
+        }
+    }
+
+    fn has_insufficient_material(&self, board: &Board) -> bool {
+        board.has_insufficient_material()
+    }
+
+    /// Reads a position, the variant of the returned board is set by the caller.
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        Board::parse_fen(fen)
+    }
+
+    fn write_fen(&self, board: &Board) -> String {
+        board.standard_fen()
+    }
+}
+
+/// Standard chess.
+pub struct Standard;
+
+impl Variant for Standard {
+    fn name(&self) -> &'static str {
+        "chess"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Standard"
+    }
+
+    fn xboard_name(&self) -> &'static str {
+        "normal"
+    }
+
+    fn start_position(&self) -> Board {
+        Board::new()
+    }
+}
+
+/// Fischer Random Chess: standard rules with a shuffled back rank.
+/// The start position is the standard one (#518), use `Board::new_chess960()` for others.
+pub struct Chess960;
+
+impl Variant for Chess960 {
+    fn name(&self) -> &'static str {
+        "chess960"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Chess960"
+    }
+
+    fn xboard_name(&self) -> &'static str {
+        "fischerandom"
+    }
+
+    fn is_chess960(&self) -> bool {
+        true
+    }
+
+    fn start_position(&self) -> Board {
+        Board::new_chess960(518)
+    }
+}
+
+/// Crazyhouse: captured pieces change sides and may be dropped back on the board
+/// instead of moving. Promoted pieces are captured as pawns.
+/// FEN carries the pockets in brackets (`[Qn]`) and marks promoted pieces with `~`.
+pub struct Crazyhouse;
+
+impl Variant for Crazyhouse {
+    fn name(&self) -> &'static str {
+        "crazyhouse"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Crazyhouse"
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant(START_FEN, &CRAZYHOUSE).unwrap()
+    }
+
+    fn captures_to_pocket(&self) -> bool {
+        true
+    }
+
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        standard_pseudo_legal_moves(board, kinds, res);
+        if kinds.has_quiets() {
+            drop_moves(board, res);
+        }
+    }
+
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        read_pocket_fen(fen, self.geometry())
+    }
+
+    fn write_fen(&self, board: &Board) -> String {
+        write_pocket_fen(board)
+    }
+}
+
+/// A board of a bughouse game: Crazyhouse drops, but captured pieces go to the partner
+/// on the other board (see `bughouse::BughouseGame`), never into the own pocket.
+pub struct Bughouse;
+
+impl Variant for Bughouse {
+    fn name(&self) -> &'static str {
+        "bughouse"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Bughouse"
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant(START_FEN, &BUGHOUSE).unwrap()
+    }
+
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        standard_pseudo_legal_moves(board, kinds, res);
+        if kinds.has_quiets() {
+            drop_moves(board, res);
+        }
+    }
+
+    /// Pieces may still arrive from the partner.
+    fn has_insufficient_material(&self, _board: &Board) -> bool {
+        false
+    }
+
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        read_pocket_fen(fen, self.geometry())
+    }
+
+    fn write_fen(&self, board: &Board) -> String {
+        write_pocket_fen(board)
+    }
+}
+
+/// Atomic chess: a capture explodes the capturing and the captured piece together with
+/// all pieces but pawns around the target square. Exploding the enemy king wins,
+/// kings can't capture and adjacent kings don't give check.
+pub struct Atomic;
+
+impl Variant for Atomic {
+    fn name(&self) -> &'static str {
+        "atomic"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Atomic"
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant(START_FEN, &ATOMIC).unwrap()
+    }
+
+    fn explodes(&self) -> bool {
+        true
+    }
+
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        standard_pseudo_legal_moves(board, kinds, res);
+        res.retain(|m| {
+            m.get_moving_piece().get_kind() != PieceKind::King || m.get_captured_piece().is_none()
+        });
+    }
+
+    fn is_pseudo_legal(&self, board: &Board, m: Move) -> bool {
+        standard_is_pseudo_legal(board, m, &STANDARD_PROMOTIONS) &&
+        (m.get_moving_piece().get_kind() != PieceKind::King || m.get_captured_piece().is_none())
+    }
+
+    /// Blowing up the own king is illegal, blowing up the enemy one is always allowed.
+    fn is_legal(&self, after: &Board, m: Move) -> bool {
+        let color = m.get_moving_piece().get_color();
+        if !after.has_king(color) {
+            return false;
+        }
+        !after.has_king(color.opposite()) || !is_atomic_check(after, color)
+    }
+
+    fn is_in_check(&self, board: &Board) -> bool {
+        let color = board.get_side_to_move();
+        board.has_king(color) && board.has_king(color.opposite()) &&
+        is_atomic_check(board, color)
+    }
+
+    fn is_king_square_attacked(&self, board: &Board, s: Square, color: Color) -> bool {
+        board.has_king(color.opposite()) && is_atomic_attack(board, s, color)
+    }
+
+    fn special_outcome(&self, board: &Board) -> Option<Outcome> {
+        let color = board.get_side_to_move();
+        if board.has_king(color) {
+            None
+        } else {
+            Some(Outcome::VariantWin(color.opposite()))
+        }
+    }
+}
+
+/// Check in Atomic: only pieces other than the king attack, and not while the kings touch.
+fn is_atomic_check(board: &Board, color: Color) -> bool {
+    is_atomic_attack(board, board.get_king_square(color), color)
+}
+
+/// Returns `true` if a king of `color` on the square `s` would be in check in Atomic.
+fn is_atomic_attack(board: &Board, king: Square, color: Color) -> bool {
+    let enemy_king = board.get_king_square(color.opposite());
+    if (king.get_file() as i32 - enemy_king.get_file() as i32).abs() <= 1 &&
+       (king.get_rank() as i32 - enemy_king.get_rank() as i32).abs() <= 1 {
+        return false;
+    }
+    board.least_valuable_attacker_kind(king, color.opposite())
+        .is_some_and(|k| k != PieceKind::King)
+}
+
+/// Three-check: giving the third check wins.
+/// FEN ends with the checks given so far (`+1+0`), the remaining ones (`2+3` before
+/// the counters, as written by Lichess) are accepted as well.
+pub struct ThreeCheck;
+
+impl Variant for ThreeCheck {
+    fn name(&self) -> &'static str {
+        "3check"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Three-check"
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant(START_FEN, &THREE_CHECK).unwrap()
+    }
+
+    fn counts_checks(&self) -> bool {
+        true
+    }
+
+    fn special_outcome(&self, board: &Board) -> Option<Outcome> {
+        [Color::White, Color::Black]
+            .iter()
+            .find(|&&c| board.get_checks_remaining(c) == 0)
+            .map(|&c| Outcome::VariantWin(c))
+    }
+
+    /// Any piece can give checks, so only bare kings are a draw.
+    fn has_insufficient_material(&self, board: &Board) -> bool {
+        board.has_insufficient_material() &&
+        board.get_geometry().squares().all(|s| {
+            board.get_piece(s).is_none_or(|p| p.get_kind() == PieceKind::King)
+        })
+    }
+
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        let mut fields: Vec<&str> = fen.split_whitespace().collect();
+        let mut remaining = [3, 3];
+        if fields.last().is_some_and(|f| f.starts_with('+')) {
+            let given = parse_checks(fields.pop().unwrap())?;
+            remaining = [3 - given[0], 3 - given[1]];
+        }
+        if let Some(i) = (4..fields.len()).find(|&i| fields[i].contains('+')) {
+            remaining = parse_checks(fields.remove(i))?;
+        }
+        let mut b = Board::parse_fen(&fields.join(" "))?;
+        b.set_checks_remaining(Color::White, remaining[0]);
+        b.set_checks_remaining(Color::Black, remaining[1]);
+        Ok(b)
+    }
+
+    fn write_fen(&self, board: &Board) -> String {
+        format!("{} +{}+{}",
+                board.standard_fen(),
+                3 - board.get_checks_remaining(Color::White),
+                3 - board.get_checks_remaining(Color::Black))
+    }
+}
+
+/// Parses check counts for White and Black, like `+1+0` or `2+3`.
+fn parse_checks(s: &str) -> Result<[u8; 2], BoardError> {
+    let error = || BoardError::Fen("Wrong check counts", s.to_string());
+    let counts: Vec<&str> = s.trim_start_matches('+').split('+').collect();
+    if counts.len() != 2 {
+        return Err(error());
+    }
+    let count = |c: &str| c.parse().ok().filter(|&n| n <= 3).ok_or_else(error);
+    Ok([count(counts[0])?, count(counts[1])?])
+}
+
+/// King of the Hill: a king reaching one of the four central squares wins.
+pub struct KingOfTheHill;
+
+impl Variant for KingOfTheHill {
+    fn name(&self) -> &'static str {
+        "kingofthehill"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "King of the Hill"
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant(START_FEN, &KING_OF_THE_HILL).unwrap()
+    }
+
+    fn special_outcome(&self, board: &Board) -> Option<Outcome> {
+        [Color::White, Color::Black]
+            .iter()
+            .find(|&&c| {
+                let s = board.get_king_square(c);
+                board.has_king(c) && [File::D, File::E].contains(&s.get_file()) &&
+                [Rank::Fourth, Rank::Fifth].contains(&s.get_rank())
+            })
+            .map(|&c| Outcome::VariantWin(c))
+    }
+
+    /// A bare king can still walk to the centre.
+    fn has_insufficient_material(&self, _board: &Board) -> bool {
+        false
+    }
+}
+
+/// Antichess (losing chess): captures are compulsory and kings are ordinary pieces,
+/// so there is no check or castling and pawns may promote to kings.
+/// A side without pieces or without moves wins.
+pub struct Antichess;
+
+impl Variant for Antichess {
+    fn name(&self) -> &'static str {
+        "antichess"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Antichess"
+    }
+
+    fn xboard_name(&self) -> &'static str {
+        "giveaway"
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+                                &ANTICHESS)
+            .unwrap()
+    }
+
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        standard_pseudo_legal_moves(board, kinds, res);
+        res.retain(|m| !m.is_castling());
+        add_promotions(res, &[PieceKind::King]);
+    }
+
+    /// All pseudo-legal moves are legal, so the compulsory captures are checked here.
+    fn is_pseudo_legal(&self, board: &Board, m: Move) -> bool {
+        let promotions = [PieceKind::Knight, PieceKind::Bishop, PieceKind::Rook, PieceKind::Queen,
+                          PieceKind::King];
+        if m.is_castling() || !standard_is_pseudo_legal(board, m, &promotions) {
+            return false;
+        }
+        m.get_captured_piece().is_some() || !has_capture(board)
+    }
+
+    fn is_legal(&self, _after: &Board, _m: Move) -> bool {
+        true
+    }
+
+    /// Captures are compulsory.
+    fn filter_legal_moves(&self, board: &Board, moves: &mut MoveList) {
+        if has_capture(board) {
+            moves.retain(|m| m.get_captured_piece().is_some());
+        }
+    }
+
+    fn is_in_check(&self, _board: &Board) -> bool {
+        false
+    }
+
+    fn special_outcome(&self, board: &Board) -> Option<Outcome> {
+        let color = board.get_side_to_move();
+        let has_pieces = board.get_geometry().squares().any(|s| {
+            board.get_piece(s).is_some_and(|p| p.get_color() == color)
+        });
+        if has_pieces {
+            None
+        } else {
+            Some(Outcome::VariantWin(color))
+        }
+    }
+
+    fn no_moves_outcome(&self, board: &Board) -> Outcome {
+        Outcome::VariantWin(board.get_side_to_move())
+    }
+
+    fn has_insufficient_material(&self, _board: &Board) -> bool {
+        false
+    }
+
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        Board::parse_fen_relaxed(fen)
+    }
+}
+
+/// Returns `true` if the side to move can capture anything, en passant included.
+fn has_capture(board: &Board) -> bool {
+    let color = board.get_side_to_move();
+    let en_passant = board.get_en_passant_square()
+        .is_some_and(|s| board.least_valuable_attacker_kind(s, color) == Some(PieceKind::Pawn));
+    en_passant ||
+    board.get_geometry().squares().any(|s| {
+        board.get_piece(s).is_some_and(|p| p.get_color() != color) &&
+        board.is_square_attacked(s, color)
+    })
+}
+
+/// Horde: White has 36 pawns and no king, and loses when all its pieces are captured.
+/// Pawns on the first rank may step two squares as well.
+pub struct Horde;
+
+impl Variant for Horde {
+    fn name(&self) -> &'static str {
+        "horde"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Horde"
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant("rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/\
+                                 PPPPPPPP w kq - 0 1",
+                                &HORDE)
+            .unwrap()
+    }
+
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        standard_pseudo_legal_moves(board, kinds, res);
+        if !kinds.has_quiets() {
+            return;
+        }
+        let geometry = board.get_geometry();
+        let rank = geometry.back_rank(board.get_side_to_move());
+        for f in 0..geometry.get_files() as u32 {
+            let from = Square::from_file_and_rank(File::from_int(f), rank);
+            if let Some(m) = first_rank_double_step(board, from) {
+                res.push(m);
+            }
+        }
+    }
+
+    fn is_pseudo_legal(&self, board: &Board, m: Move) -> bool {
+        standard_is_pseudo_legal(board, m, &STANDARD_PROMOTIONS) ||
+        !m.is_drop() && first_rank_double_step(board, m.get_square_from()) == Some(m)
+    }
+
+    fn special_outcome(&self, board: &Board) -> Option<Outcome> {
+        let white_left = board.get_geometry().squares().any(|s| {
+            board.get_piece(s).is_some_and(|p| p.get_color() == Color::White)
+        });
+        if white_left {
+            None
+        } else {
+            Some(Outcome::VariantWin(Color::Black))
+        }
+    }
+
+    /// Black can always win by capturing everything.
+    fn has_insufficient_material(&self, _board: &Board) -> bool {
+        false
+    }
+
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        Board::parse_fen_first_rank_pawns(fen, Color::White)
+    }
+}
+
+/// The double step of a pawn of the side to move from its first rank in Horde, if possible.
+fn first_rank_double_step(board: &Board, from: Square) -> Option<Move> {
+    let color = board.get_side_to_move();
+    let pawn = Piece::new(PieceKind::Pawn, color);
+    let geometry = board.get_geometry();
+    if !geometry.contains(from) || from.get_rank() != geometry.back_rank(color) ||
+       board.get_piece(from) != Some(pawn) {
+        return None;
+    }
+    let over = from.get_forward_in(color, geometry)?;
+    let to = over.get_forward_in(color, geometry)?;
+    if board.get_piece(over).is_none() && board.get_piece(to).is_none() {
+        Some(Move::new(pawn, from, to, None, None, false, false))
+    } else {
+        None
+    }
+}
+
+/// Racing Kings: giving check is forbidden and the first king to reach the eighth rank
+/// wins. If White gets there first, Black may still draw by reaching it the next move.
+pub struct RacingKings;
+
+impl Variant for RacingKings {
+    fn name(&self) -> &'static str {
+        "racingkings"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Racing Kings"
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant("8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1", &RACING_KINGS)
+            .unwrap()
+    }
+
+    fn is_legal(&self, after: &Board, m: Move) -> bool {
+        let color = m.get_moving_piece().get_color();
+        !after.is_square_attacked(after.get_king_square(color), color.opposite()) &&
+        !after.is_in_check()
+    }
+
+    fn special_outcome(&self, board: &Board) -> Option<Outcome> {
+        let at_goal = |c: Color| board.get_king_square(c).get_rank() == Rank::Eighth;
+        match (at_goal(Color::White), at_goal(Color::Black)) {
+            (true, true) => Some(Outcome::VariantDraw),
+            (false, true) => Some(Outcome::VariantWin(Color::Black)),
+            (true, false) if board.get_side_to_move() == Color::Black &&
+                             self.can_reach_goal(board) => None,
+            (true, false) => Some(Outcome::VariantWin(Color::White)),
+            (false, false) => None,
+        }
+    }
+
+    /// Kings alone can still race.
+    fn has_insufficient_material(&self, _board: &Board) -> bool {
+        false
+    }
+}
+
+impl RacingKings {
+    /// Returns `true` if the side to move can bring its king to the eighth rank.
+    fn can_reach_goal(&self, board: &Board) -> bool {
+        let mut moves = MoveList::new();
+        standard_pseudo_legal_moves(board, MoveKinds::All, &mut moves);
+        moves.into_iter().any(|m| {
+            m.get_moving_piece().get_kind() == PieceKind::King &&
+            m.get_square_to().get_rank() == Rank::Eighth &&
+            board.do_move(m).is_ok_and(|after| self.is_legal(&after, m))
+        })
+    }
+}
+
+/// Capablanca chess: a 10x8 board with an archbishop (moving like a bishop or a knight) and
+/// a chancellor (a rook or a knight) on each side, which pawns may promote to as well.
+/// Castling takes the king to the C or the I file.
+pub struct Capablanca;
+
+impl Variant for Capablanca {
+    fn name(&self) -> &'static str {
+        "capablanca"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Capablanca"
+    }
+
+    fn geometry(&self) -> Geometry {
+        Geometry::CAPABLANCA
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant(CAPABLANCA_FEN, &CAPABLANCA).unwrap()
+    }
+
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        capablanca_pseudo_legal_moves(board, kinds, res);
+    }
+
+    fn is_pseudo_legal(&self, board: &Board, m: Move) -> bool {
+        standard_is_pseudo_legal(board, m, &CAPABLANCA_PROMOTIONS)
+    }
+
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        Board::parse_fen_geometry(fen, self.geometry())
+    }
+}
+
+/// Gothic chess: Capablanca chess with another setup.
+pub struct Gothic;
+
+impl Variant for Gothic {
+    fn name(&self) -> &'static str {
+        "gothic"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Gothic"
+    }
+
+    fn geometry(&self) -> Geometry {
+        Geometry::CAPABLANCA
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant(GOTHIC_FEN, &GOTHIC).unwrap()
+    }
+
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        capablanca_pseudo_legal_moves(board, kinds, res);
+    }
+
+    fn is_pseudo_legal(&self, board: &Board, m: Move) -> bool {
+        standard_is_pseudo_legal(board, m, &CAPABLANCA_PROMOTIONS)
+    }
+
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        Board::parse_fen_geometry(fen, self.geometry())
+    }
+}
+
+/// Capablanca Random: Capablanca chess with a shuffled back rank and Chess960 castling.
+/// The start position is the Gothic one, use `Board::new_capablanca_random()` for others.
+pub struct CapablancaRandom;
+
+impl Variant for CapablancaRandom {
+    fn name(&self) -> &'static str {
+        "caparandom"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Capablanca Random"
+    }
+
+    fn is_chess960(&self) -> bool {
+        true
+    }
+
+    fn geometry(&self) -> Geometry {
+        Geometry::CAPABLANCA
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant(GOTHIC_FEN, &CAPABLANCA_RANDOM).unwrap()
+    }
+
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        capablanca_pseudo_legal_moves(board, kinds, res);
+    }
+
+    fn is_pseudo_legal(&self, board: &Board, m: Move) -> bool {
+        standard_is_pseudo_legal(board, m, &CAPABLANCA_PROMOTIONS)
+    }
+
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        Board::parse_fen_geometry(fen, self.geometry())
+    }
+}
+
+/// Gardner minichess: a 5x5 board with the full piece set, pawns moving one square only
+/// and no castling.
+pub struct Gardner;
+
+impl Variant for Gardner {
+    fn name(&self) -> &'static str {
+        "gardner"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Gardner"
+    }
+
+    fn geometry(&self) -> Geometry {
+        Geometry::GARDNER
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant("rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1", &GARDNER).unwrap()
+    }
+
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        minichess_pseudo_legal_moves(board, kinds, res, &STANDARD_PROMOTIONS);
+    }
+
+    fn is_pseudo_legal(&self, board: &Board, m: Move) -> bool {
+        minichess_is_pseudo_legal(board, m, &STANDARD_PROMOTIONS)
+    }
+
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        Board::parse_fen_geometry(fen, self.geometry())
+    }
+}
+
+/// Los Alamos chess: a 6x6 board without bishops, pawns moving one square only (and not
+/// promoting to bishops) and no castling.
+pub struct LosAlamos;
+
+impl Variant for LosAlamos {
+    fn name(&self) -> &'static str {
+        "losalamos"
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        "Los Alamos"
+    }
+
+    fn geometry(&self) -> Geometry {
+        Geometry::LOS_ALAMOS
+    }
+
+    fn start_position(&self) -> Board {
+        Board::from_fen_variant("rnqknr/pppppp/6/6/PPPPPP/RNQKNR w - - 0 1", &LOS_ALAMOS)
+            .unwrap()
+    }
+
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        minichess_pseudo_legal_moves(board, kinds, res, &LOS_ALAMOS_PROMOTIONS);
+    }
+
+    fn is_pseudo_legal(&self, board: &Board, m: Move) -> bool {
+        minichess_is_pseudo_legal(board, m, &LOS_ALAMOS_PROMOTIONS)
+    }
+
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        Board::parse_fen_geometry(fen, self.geometry())
+    }
+}
+
+const LOS_ALAMOS_PROMOTIONS: [PieceKind; 3] = [PieceKind::Knight, PieceKind::Rook,
+                                                PieceKind::Queen];
+
+/// Standard moves without double pawn steps and castling, with promotions to the given kinds.
+fn minichess_pseudo_legal_moves(board: &Board,
+                                kinds: MoveKinds,
+                                res: &mut MoveList,
+                                promotions: &[PieceKind]) {
+    standard_pseudo_legal_moves(board, kinds, res);
+    res.retain(|&m| is_minichess_move(m, promotions));
+}
+
+fn minichess_is_pseudo_legal(board: &Board, m: Move, promotions: &[PieceKind]) -> bool {
+    is_minichess_move(m, promotions) && standard_is_pseudo_legal(board, m, promotions)
+}
+
+fn is_minichess_move(m: Move, promotions: &[PieceKind]) -> bool {
+    let ranks = m.get_square_to().get_rank() as i32 - m.get_square_from().get_rank() as i32;
+    let double_step = m.get_moving_piece().get_kind() == PieceKind::Pawn && ranks.abs() == 2;
+    !double_step && !m.is_castling() && m.get_promoted_to().is_none_or(|k| promotions.contains(&k))
+}
+
+/// Standard chess with pieces moving by user-defined rules in Betza notation (see `betza`),
+/// for prototyping variants. Pawns, kings and castling keep the standard rules.
+/// Pawns may also promote to a redefined archbishop or chancellor.
+pub struct Fairy {
+    name: &'static str,
+    geometry: Geometry,
+    start_fen: String,
+    /// Rules of each piece kind, the standard ones unless redefined.
+    rules: Vec<PieceRules>,
+    redefined: Vec<PieceKind>,
+    /// The standard promotions, then the redefined archbishop and chancellor.
+    promotions: Vec<PieceKind>,
+}
+
+impl Fairy {
+    /// Defines a variant where the given piece kinds move by rules in Betza notation.
+    /// Boards refer to their variant for the whole program, so the definition is leaked.
+    pub fn new(name: &'static str,
+               geometry: Geometry,
+               start_fen: &str,
+               pieces: &[(PieceKind, &str)])
+               -> Result<&'static Fairy, BoardError> {
+        let standard = ["fmWfcF", "N", "B", "R", "Q", "K", "BN", "RN"];
+        let mut rules = Vec::new();
+        for notation in &standard {
+            rules.push(PieceRules::from_betza(notation)?);
+        }
+        let mut redefined = Vec::new();
+        for &(kind, notation) in pieces {
+            if kind == PieceKind::Pawn || kind == PieceKind::King {
+                return Err(BoardError::Betza("Pawns and kings can't be redefined",
+                                             notation.to_string()));
+            }
+            rules[kind as usize] = PieceRules::from_betza(notation)?;
+            redefined.push(kind);
+        }
+        let mut promotions = STANDARD_PROMOTIONS.to_vec();
+        promotions.extend(redefined.iter()
+            .filter(|&&k| k == PieceKind::Archbishop || k == PieceKind::Chancellor));
+        Board::parse_fen_geometry(start_fen, geometry)?;
+        Ok(Box::leak(Box::new(Fairy {
+            name,
+            geometry,
+            start_fen: start_fen.to_string(),
+            rules,
+            redefined,
+            promotions,
+        })))
+    }
+
+    /// Returns the rules a piece kind moves by.
+    pub fn get_rules(&self, kind: PieceKind) -> &PieceRules {
+        &self.rules[kind as usize]
+    }
+}
+
+impl Variant for Fairy {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn pgn_name(&self) -> &'static str {
+        self.name
+    }
+
+    fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    fn start_position(&'static self) -> Board {
+        Board::from_fen_variant(&self.start_fen, self).unwrap()
+    }
+
+    fn pseudo_legal_moves(&self, board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+        standard_pseudo_legal_moves(board, kinds, res);
+        res.retain(|m| !self.redefined.contains(&m.get_moving_piece().get_kind()));
+        add_promotions(res, &self.promotions[STANDARD_PROMOTIONS.len()..]);
+        for s in self.geometry.squares() {
+            match board.get_piece(s) {
+                Some(p) if p.get_color() == board.get_side_to_move() &&
+                           self.redefined.contains(&p.get_kind()) => {
+                    fairy_moves(board, s, self.get_rules(p.get_kind()), res);
+                }
+                _ => {}
+            }
+        }
+        if kinds != MoveKinds::All {
+            res.retain(|&m| kinds.contains(m));
+        }
+    }
+
+    fn is_pseudo_legal(&self, board: &Board, m: Move) -> bool {
+        let kind = m.get_moving_piece().get_kind();
+        if self.redefined.contains(&kind) {
+            fairy_is_pseudo_legal(board, m, self.get_rules(kind))
+        } else {
+            standard_is_pseudo_legal(board, m, &self.promotions)
+        }
+    }
+
+    fn is_square_attacked(&self, board: &Board, s: Square, by: Color) -> bool {
+        self.geometry.squares().any(|from| match board.get_piece(from) {
+            Some(p) if p.get_color() == by => {
+                fairy_attacks(board, from, by, self.get_rules(p.get_kind()), s)
+            }
+            _ => false,
+        })
+    }
+
+    fn read_fen(&self, fen: &str) -> Result<Board, BoardError> {
+        Board::parse_fen_geometry(fen, self.geometry)
+    }
+}
+
+const CAPABLANCA_PROMOTIONS: [PieceKind; 6] = [PieceKind::Knight, PieceKind::Bishop,
+                                                PieceKind::Rook, PieceKind::Queen,
+                                                PieceKind::Archbishop, PieceKind::Chancellor];
+
+/// Standard moves with promotions to the archbishop and the chancellor as well.
+fn capablanca_pseudo_legal_moves(board: &Board, kinds: MoveKinds, res: &mut MoveList) {
+    standard_pseudo_legal_moves(board, kinds, res);
+    add_promotions(res, &[PieceKind::Archbishop, PieceKind::Chancellor]);
+}
+
+/// Adds promotions to the given kinds for every promotion to a queen in `res`.
+fn add_promotions(res: &mut MoveList, kinds: &[PieceKind]) {
+    for i in 0..res.len() {
+        let m = res[i];
+        if m.get_promoted_to() == Some(PieceKind::Queen) {
+            for &kind in kinds {
+                res.push(Move::new(m.get_moving_piece(),
+                                   m.get_square_from(),
+                                   m.get_square_to(),
+                                   m.get_captured_piece(),
+                                   Some(kind),
+                                   false,
+                                   false));
+            }
+        }
+    }
+}
+
+/// Most pieces of one kind a pocket may hold in FEN.
+const MAX_FEN_POCKET: u8 = 32;
+
+/// Reads FEN with pockets, given either in brackets after the placement (`.../RNBQKBNR[Qn]`)
+/// or as a ninth rank (`.../RNBQKBNR/Qn`), and promoted pieces followed by `~`.
+fn read_pocket_fen(fen: &str, geometry: Geometry) -> Result<Board, BoardError> {
+    let fen = fen.trim();
+    let (placement, rest) = fen.split_at(fen.find(' ').unwrap_or(fen.len()));
+    let (placement, pocket) = if placement.ends_with(']') {
+        let open = placement.find('[')
+            .ok_or_else(|| BoardError::Fen("Wrong pocket", placement.to_string()))?;
+        (&placement[..open], &placement[open + 1..placement.len() - 1])
+    } else if placement.matches('/').count() == geometry.get_ranks() as usize {
+        placement.split_at(placement.rfind('/').unwrap())
+    } else {
+        (placement, "")
+    };
+    let mut promoted = Vec::new();
+    let (mut rank, mut file) = (geometry.get_ranks() as i32 - 1, 0);
+    // the number of empty squares read so far, in case it has more digits
+    let mut empty = 0;
+    for c in placement.chars() {
+        match c {
+            '/' => {
+                rank -= 1;
+                file = 0;
+            }
+            '~' if file > 0 && file <= geometry.get_files() as u32 && rank >= 0 => {
+                promoted.push(Square::from_file_and_rank(File::from_int(file - 1),
+                                                         Rank::from_int(rank as u32)));
+            }
+            '~' => return Err(BoardError::Fen("Wrong promoted piece", placement.to_string())),
+            c if c.is_ascii_digit() => {
+                file -= empty;
+                empty = empty * 10 + c.to_digit(10).unwrap();
+                if empty > geometry.get_files() as u32 {
+                    return Err(BoardError::Fen("Wrong number of files", placement.to_string()));
+                }
+                file += empty;
+                continue;
+            }
+            _ => file += 1,
+        }
+        empty = 0;
+    }
+    let fen = format!("{}{}", placement.replace('~', ""), rest);
+    let mut b = Board::parse_fen_geometry(&fen, geometry)?;
+    for s in promoted {
+        b.set_promoted(s);
+    }
+    for c in pocket.trim_start_matches('/').chars().filter(|&c| c != '-') {
+        let error = || BoardError::Fen("Wrong pocket", pocket.to_string());
+        let p = piece_from_char(c).ok_or_else(error)?;
+        if p.get_kind() == PieceKind::King {
+            return Err(error());
+        }
+        if b.get_pocket(p.get_color(), p.get_kind()) >= MAX_FEN_POCKET {
+            return Err(BoardError::Fen("Too many pieces in a pocket", pocket.to_string()));
+        }
+        b.add_to_pocket(p.get_color(), p.get_kind())?;
+    }
+    Ok(b)
+}
+
+/// Writes FEN with pockets in brackets and promoted pieces marked with `~`.
+fn write_pocket_fen(board: &Board) -> String {
+    let fen = board.standard_fen();
+    let rest = &fen[fen.find(' ').unwrap()..];
+    let mut res = board.placement_fen(true);
+    res.push('[');
+    for &color in &[Color::White, Color::Black] {
+        for &kind in &[PieceKind::Queen, PieceKind::Rook, PieceKind::Bishop, PieceKind::Knight,
+                       PieceKind::Pawn] {
+            for _ in 0..board.get_pocket(color, kind) {
+                res.push(piece_to_char(Piece::new(kind, color)));
+            }
+        }
+    }
+    res.push(']');
+    res.push_str(rest);
+    res
+}
+
+pub static STANDARD: Standard = Standard;
+pub static CHESS960: Chess960 = Chess960;
+pub static CRAZYHOUSE: Crazyhouse = Crazyhouse;
+pub static BUGHOUSE: Bughouse = Bughouse;
+pub static ATOMIC: Atomic = Atomic;
+pub static THREE_CHECK: ThreeCheck = ThreeCheck;
+pub static KING_OF_THE_HILL: KingOfTheHill = KingOfTheHill;
+pub static ANTICHESS: Antichess = Antichess;
+pub static HORDE: Horde = Horde;
+pub static RACING_KINGS: RacingKings = RacingKings;
+pub static CAPABLANCA: Capablanca = Capablanca;
+pub static GOTHIC: Gothic = Gothic;
+pub static CAPABLANCA_RANDOM: CapablancaRandom = CapablancaRandom;
+pub static GARDNER: Gardner = Gardner;
+pub static LOS_ALAMOS: LosAlamos = LosAlamos;
+
+static VARIANTS: [&'static dyn Variant; 15] = [&STANDARD, &CHESS960, &CRAZYHOUSE, &BUGHOUSE,
+                                               &ATOMIC, &THREE_CHECK, &KING_OF_THE_HILL,
+                                               &ANTICHESS, &HORDE, &RACING_KINGS, &CAPABLANCA,
+                                               &GOTHIC, &CAPABLANCA_RANDOM, &GARDNER,
+                                               &LOS_ALAMOS];
+
+/// All supported variants.
+pub fn variants() -> &'static [&'static dyn Variant] {
+    &VARIANTS
+}
+
+/// Finds a variant by its UCI, PGN or XBoard name (ignoring case).
+pub fn variant_by_name(name: &str) -> Option<&'static dyn Variant> {
+    let name = name.to_lowercase();
+    VARIANTS.iter().cloned().find(|v| {
+        [v.name(), v.pgn_name(), v.xboard_name()].iter().any(|n| n.to_lowercase() == name)
+    })
+}
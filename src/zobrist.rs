@@ -0,0 +1,111 @@
+use ::piece::*;
+use ::square::*;
+
+/// Random keys for Zobrist hashing, generated at compile time.
+pub struct Keys {
+    pieces: [[u64; SQUARE_COUNT]; 2 * KIND_COUNT],
+    castling: [u64; 4],
+    en_passant: [u64; MAX_FILES],
+    side: u64,
+    /// Keys for the n-th piece of a kind in a pocket (Crazyhouse), for any count it may hold.
+    pockets: [[u64; POCKET_KEYS]; 2 * KIND_COUNT],
+    /// Keys for the checks a side still has to give (Three-check), fewer than three.
+    checks: [[u64; 3]; 2],
+}
+
+const POCKET_KEYS: usize = u8::MAX as usize;
+
+pub static KEYS: Keys = generate(0x9E37_79B9_7F4A_7C15);
+
+const fn xorshift(mut x: u64) -> u64 {
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    x
+}
+
+const fn generate(seed: u64) -> Keys {
+    let mut keys = Keys {
+        pieces: [[0; SQUARE_COUNT]; 2 * KIND_COUNT],
+        castling: [0; 4],
+        en_passant: [0; MAX_FILES],
+        side: 0,
+        pockets: [[0; POCKET_KEYS]; 2 * KIND_COUNT],
+        checks: [[0; 3]; 2],
+    };
+    let mut x = seed;
+    let mut i = 0;
+    while i < 2 * KIND_COUNT {
+        let mut j = 0;
+        while j < SQUARE_COUNT {
+            x = xorshift(x);
+            keys.pieces[i][j] = x;
+            j += 1;
+        }
+        i += 1;
+    }
+    i = 0;
+    while i < 4 {
+        x = xorshift(x);
+        keys.castling[i] = x;
+        i += 1;
+    }
+    i = 0;
+    while i < MAX_FILES {
+        x = xorshift(x);
+        keys.en_passant[i] = x;
+        i += 1;
+    }
+    x = xorshift(x);
+    keys.side = x;
+    i = 0;
+    while i < 2 * KIND_COUNT {
+        let mut j = 0;
+        while j < POCKET_KEYS {
+            x = xorshift(x);
+            keys.pockets[i][j] = x;
+            j += 1;
+        }
+        i += 1;
+    }
+    i = 0;
+    while i < 2 {
+        let mut j = 0;
+        while j < 3 {
+            x = xorshift(x);
+            keys.checks[i][j] = x;
+            j += 1;
+        }
+        i += 1;
+    }
+    keys
+}
+
+impl Keys {
+    pub fn piece(&self, p: Piece, s: Square) -> u64 {
+        self.pieces[p.get_kind() as usize + KIND_COUNT * p.get_color() as usize][s.as_index()]
+    }
+
+    pub fn castling(&self, index: usize) -> u64 {
+        self.castling[index]
+    }
+
+    pub fn en_passant(&self, s: Square) -> u64 {
+        self.en_passant[s.get_file() as usize]
+    }
+
+    pub fn side(&self) -> u64 {
+        self.side
+    }
+
+    /// Key of the number of checks a side still has to give (zero for three or more).
+    pub fn checks(&self, color: Color, remaining: u8) -> u64 {
+        self.checks[color as usize].get(remaining as usize).cloned().unwrap_or(0)
+    }
+
+    /// Key of a pocket holding `count` pieces `p`.
+    pub fn pocket(&self, p: Piece, count: u8) -> u64 {
+        let keys = &self.pockets[p.get_kind() as usize + KIND_COUNT * p.get_color() as usize];
+        keys[..count as usize].iter().fold(0, |h, k| h ^ k)
+    }
+}
@@ -0,0 +1,138 @@
+extern crate rusty_board;
+use rusty_board::betza::*;
+use rusty_board::board::*;
+use rusty_board::generator::*;
+use rusty_board::moves::*;
+use rusty_board::piece::*;
+use rusty_board::square::*;
+use rusty_board::variant::*;
+
+fn leaps(notation: &str) -> Vec<(i32, i32, u32, LeapMode)> {
+    let mut res: Vec<(i32, i32, u32, LeapMode)> = PieceRules::from_betza(notation)
+        .unwrap()
+        .get_leaps()
+        .iter()
+        .map(|l| (l.get_files(), l.get_ranks(), l.get_range(), l.get_mode()))
+        .collect();
+    res.sort_by_key(|l| (l.0, l.1, l.2));
+    res
+}
+
+#[test]
+fn test_betza_parsing() {
+    assert_eq!(leaps("WD").len(), 8);
+    assert_eq!(leaps("N").len(), 8);
+    assert!(leaps("NB").iter().any(|l| l.2 == u32::MAX));
+    assert!(leaps("NN").iter().all(|l| l.2 == u32::MAX));
+    assert_eq!(leaps("R1"), leaps("W"));
+    assert_eq!(leaps("Q1"), leaps("K"));
+    assert!(leaps("R4").iter().all(|l| l.2 == 4));
+    assert_eq!(leaps("mWcF").iter().filter(|l| l.3 == LeapMode::Move).count(), 4);
+    assert_eq!(leaps("mWcF").iter().filter(|l| l.3 == LeapMode::Capture).count(), 4);
+    assert_eq!(leaps("mWcW"), leaps("W"));
+    // shorter leaps on a rider's step are covered by the rider
+    assert_eq!(leaps("WR"), leaps("R"));
+    assert_eq!(leaps("R4R"), leaps("R"));
+    assert_eq!(leaps("KR"), leaps("FR"));
+    // only the part of a shorter leap the rider doesn't cover is kept
+    assert_eq!(leaps("mRW"), leaps("mRcW"));
+    assert_eq!(leaps("cRmW").len(), 8);
+    assert_eq!(leaps("K"), leaps("WF"));
+    // directions
+    assert_eq!(leaps("fW"), vec![(0, 1, 1, LeapMode::Both)]);
+    assert_eq!(leaps("frF"), vec![(1, 1, 1, LeapMode::Both)]);
+    assert_eq!(leaps("ffN"), vec![(-1, 2, 1, LeapMode::Both), (1, 2, 1, LeapMode::Both)]);
+    assert_eq!(leaps("sW"), leaps("lWrW"));
+    assert_eq!(leaps("vR").len(), 2);
+    assert!(PieceRules::from_betza("").is_err());
+    assert!(PieceRules::from_betza("fm").is_err());
+    assert!(PieceRules::from_betza("X").is_err());
+    assert!(PieceRules::from_betza("R0").is_err());
+}
+
+#[test]
+fn test_fairy_variant() {
+    // the compound pieces of Capablanca chess
+    let capa = Fairy::new("fairy-capablanca",
+                          Geometry::CAPABLANCA,
+                          "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1",
+                          &[(PieceKind::Archbishop, "BN"), (PieceKind::Chancellor, "RN")])
+        .unwrap();
+    assert_eq!(perft(Board::new_variant(capa), 3), 25228);
+    // standard pieces redefined by their own rules change nothing
+    let chess = Fairy::new("fairy-chess",
+                           Geometry::STANDARD,
+                           "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                           &[(PieceKind::Knight, "N"), (PieceKind::Queen, "RB")])
+        .unwrap();
+    assert_eq!(perft(Board::new_variant(chess), 4), 197281);
+    // a knight moving like a wazir or a dababba, giving check to the king two squares ahead
+    let woody = Fairy::new("woody",
+                           Geometry::STANDARD,
+                           "4k3/8/8/8/8/8/8/1N2K3 w - - 0 1",
+                           &[(PieceKind::Knight, "WD")])
+        .unwrap();
+    let b = Board::new_variant(woody);
+    let knight = Square::from_str("b1").unwrap();
+    let mut moves = MoveList::new();
+    fairy_moves(&b, knight, woody.get_rules(PieceKind::Knight), &mut moves);
+    assert_eq!(moves.len(), 5);
+    assert_eq!(perft(b.clone(), 1), 10);
+    let b = Board::from_fen_variant("4k3/8/4N3/8/8/8/8/4K3 b - - 0 1", woody).unwrap();
+    assert!(b.is_in_check());
+    let b = Board::from_fen_variant("4k3/8/3N4/8/8/8/8/4K3 b - - 0 1", woody).unwrap();
+    assert!(!b.is_in_check());
+    // move-only and capture-only leaps
+    let b = Board::from_fen_variant("4k3/8/8/8/8/2p5/1p6/1N2K3 w - - 0 1", woody).unwrap();
+    let rules = PieceRules::from_betza("mWcF").unwrap();
+    let mut moves = MoveList::new();
+    fairy_moves(&b, knight, &rules, &mut moves);
+    let targets: Vec<String> = moves.iter().map(|m| m.get_square_to().to_string()).collect();
+    assert_eq!(targets.len(), 2);
+    assert!(targets.contains(&"a1".to_string()) && targets.contains(&"c1".to_string()));
+    // left is seen from the piece's side
+    let b = Board::from_fen_variant("4k3/8/8/3n4/3N4/8/8/4K3 w - - 0 1", woody).unwrap();
+    let rules = PieceRules::from_betza("lW").unwrap();
+    for &(from, to) in &[("d4", "c4"), ("d5", "e5")] {
+        let mut moves = MoveList::new();
+        fairy_moves(&b, Square::from_str(from).unwrap(), &rules, &mut moves);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].get_square_to().to_string(), to);
+    }
+    let (d5, e5, c5) = (Square::from_str("d5").unwrap(),
+                        Square::from_str("e5").unwrap(),
+                        Square::from_str("c5").unwrap());
+    assert!(fairy_attacks(&b, d5, Color::Black, &rules, e5));
+    assert!(!fairy_attacks(&b, d5, Color::Black, &rules, c5));
+    assert!(Fairy::new("bad", Geometry::STANDARD, "8/8/8/8/8/8/8/8 w - - 0 1",
+                       &[(PieceKind::King, "Q")])
+        .is_err());
+}
+
+#[test]
+fn test_fairy_move_validation() {
+    // asymmetric and move-only or capture-only leaps, promotions to a redefined archbishop
+    let v = Fairy::new("fairy-validation",
+                       Geometry::STANDARD,
+                       "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                       &[(PieceKind::Knight, "fNbWcF"),
+                         (PieceKind::Bishop, "mBcK"),
+                         (PieceKind::Archbishop, "BN")])
+        .unwrap();
+    let mut b = Board::from_fen_variant("rnbqkbnr/pPpppppp/8/8/8/8/PPPPPPpP/RNBQKBNR w KQkq - 0 1",
+                                        v)
+        .unwrap();
+    let mut pool: Vec<Move> = Vec::new();
+    for ply in 0..40 {
+        let pseudo = generate_pseudo_legal_moves(&b);
+        pool.extend(pseudo.iter().cloned());
+        for &m in &pool {
+            assert_eq!(b.is_pseudo_legal(m), pseudo.contains(&m), "{} {:?}", b.to_fen(), m);
+        }
+        let legal = generate_legal_moves(b.clone());
+        if legal.is_empty() {
+            break;
+        }
+        b.do_move_inplace(legal[(ply * 7) % legal.len()]).unwrap();
+    }
+}
@@ -1,4 +1,5 @@
 extern crate rusty_board;
+use rusty_board::error::*;
 use rusty_board::board::*;
 use rusty_board::square::*;
 use rusty_board::moves::*;
@@ -22,3 +23,158 @@ fn test_board() {
     let e3 = Square::from_str("e3").unwrap();
     assert_eq!(b.get_en_passant_square(), Some(e3));
 }
+
+#[test]
+fn test_fen() {
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_eq!(Board::new().to_fen(), start);
+    assert_eq!(Board::from_fen(start).unwrap().to_fen(), start);
+    let fens = ["r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 12 40",
+                // X-FEN: the B-file rook isn't the outermost one
+                "rr2k3/8/8/8/8/8/8/RR2K3 w Bb - 0 1"];
+    for fen in fens.iter() {
+        assert_eq!(Board::from_fen(fen).unwrap().to_fen(), *fen);
+    }
+    for n in 0..960 {
+        let b = Board::new_chess960(n);
+        let b2 = Board::from_fen(&b.to_fen()).unwrap();
+        assert_eq!(b2.to_fen(), b.to_fen());
+        for &c in &[Color::White, Color::Black] {
+            assert_eq!(b2.get_initial_king_file(c), b.get_initial_king_file(c));
+            assert_eq!(b2.get_initial_kings_rook_file(c), b.get_initial_kings_rook_file(c));
+            assert_eq!(b2.get_initial_queens_rook_file(c), b.get_initial_queens_rook_file(c));
+        }
+    }
+    // Shredder-FEN
+    let b = Board::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9")
+        .unwrap();
+    assert_eq!(b.get_initial_queens_rook_file(Color::Black), File::F);
+    assert_eq!(b.to_fen(), "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9");
+    assert!(Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").is_err());
+    assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq").is_err());
+    assert!(Board::from_fen("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_err());
+    assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1").is_err());
+}
+
+#[test]
+fn test_do_undo() {
+    let mut b = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+        .unwrap();
+    let fen = b.to_fen();
+    for s in ["e1g1", "e1c1", "d5e6", "a2a4", "e5f7", "a1b1"].iter() {
+        let m = b.parse_uci_move(s, false).unwrap();
+        b.do_move_inplace(m).unwrap();
+        assert_eq!(b.get_side_to_move(), Color::Black);
+        b.undo_move_inplace().unwrap();
+        assert_eq!(b.to_fen(), fen);
+    }
+    let castled = b.do_move(b.parse_uci_move("e1h1", false).unwrap()).unwrap();
+    assert_eq!(castled.to_fen(),
+               "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R4RK1 b kq - 1 1");
+    let after_a4 = b.do_move(b.parse_uci_move("a2a4", false).unwrap()).unwrap();
+    let ep = after_a4.parse_uci_move("b4a3", false).unwrap();
+    assert!(ep.is_en_passant());
+    assert_eq!(after_a4.do_move(ep).unwrap().to_fen(),
+               "r3k2r/p1ppqpb1/bn2pnp1/3PN3/4P3/p1N2Q1p/1PPBBPPP/R3K2R w KQkq - 0 2");
+    assert_eq!(Board::new().undo_move().err(), Some(MoveError::NothingToUndo));
+}
+
+#[test]
+fn test_errors() {
+    // a move of another position, with the square in question
+    let b = Board::new();
+    let m = b.parse_uci_move("e2e4", false).unwrap();
+    let after = b.do_move(m).unwrap();
+    let e2 = Square::from_str("e2").unwrap();
+    assert_eq!(after.do_move(m).err(), Some(MoveError::EmptySquare(m, e2)));
+    assert_eq!(MoveError::EmptySquare(m, e2).to_string(), "Empty starting square on e2 (e2e4)");
+    let nf3 = b.parse_uci_move("g1f3", false).unwrap();
+    let b = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKNR1 w - - 0 1").unwrap();
+    assert_eq!(b.do_move(nf3).err(),
+               Some(MoveError::WrongMovingPiece(nf3, Square::from_str("g1").unwrap())));
+    // FEN errors name the field
+    assert_eq!(Board::from_fen("rnbqkbnr/ppppxppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").err(),
+               Some(BoardError::Fen("Wrong piece letter", "ppppxppp".to_string())));
+    assert_eq!(Board::from_fen("8/8/8/8/8/8/8/99999999999 w - - 0 1").err(),
+               Some(BoardError::Fen("Wrong number of files", "99999999999".to_string())));
+    assert_eq!(Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").err(),
+               Some(BoardError::InvalidPosition("There must be exactly one king of each color")));
+    let e = Board::from_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1").err().unwrap();
+    assert_eq!(e.to_string(), "Wrong side to move in FEN: x");
+    let e: BoardError = MoveError::NothingToUndo.into();
+    assert!(std::error::Error::source(&e).is_some());
+}
+
+#[test]
+fn test_hash() {
+    let mut b = Board::new();
+    let start = b.get_hash();
+    let play = |b: &mut Board, moves: &[&str]| for s in moves {
+        let m = b.parse_uci_move(s, false).unwrap();
+        b.do_move_inplace(m).unwrap();
+    };
+    play(&mut b, &["g1f3", "g8f6", "f3g1"]);
+    assert!(!b.is_repetition());
+    play(&mut b, &["f6g8"]);
+    assert_eq!(b.get_hash(), start);
+    assert!(b.is_repetition());
+    b.undo_move_inplace().unwrap();
+    assert!(b.get_hash() != start);
+    // transpositions and positions set up from FEN hash alike
+    play(&mut b, &["f6g8", "e2e4", "d7d5", "e4d5"]);
+    assert!(!b.is_repetition());
+    let fen = b.to_fen();
+    assert_eq!(Board::from_fen(&fen).unwrap().get_hash(), b.get_hash());
+    let hash = |fen: &str| Board::from_fen(fen).unwrap().get_hash();
+    assert!(hash("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2") !=
+            hash("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"));
+}
+
+#[test]
+fn test_uci_notation() {
+    let b = Board::from_fen("1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1").unwrap();
+    let m = b.parse_uci_move("g1h1", true).unwrap();
+    assert!(m.is_castling());
+    assert_eq!(b.move_to_uci(m, true), "g1h1");
+    assert_eq!(b.move_to_uci(m, false), "g1g1");
+    assert_eq!(b.do_move(m).unwrap().to_fen(), "1r4kr/8/8/8/8/8/8/1R3RK1 b kq - 1 1");
+    let m = b.parse_uci_move("g1b1", true).unwrap();
+    assert_eq!(b.do_move(m).unwrap().to_fen(), "1r4kr/8/8/8/8/8/8/2KR3R b kq - 1 1");
+    assert_eq!(b.parse_uci_move("g1g1", true), None);
+    assert_eq!(b.parse_uci_move("e2e4", true), None);
+    let b = Board::from_fen("8/P6k/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    let m = b.parse_uci_move("a7a8n", false).unwrap();
+    assert_eq!(m.get_promoted_to(), Some(PieceKind::Knight));
+    assert_eq!(b.move_to_uci(m, false), "a7a8n");
+}
+
+#[test]
+fn test_san() {
+    let b = Board::new();
+    let san = |b: &Board, s: &str| b.move_to_san(b.parse_uci_move(s, false).unwrap());
+    assert_eq!(san(&b, "e2e4"), "e4");
+    assert_eq!(san(&b, "g1f3"), "Nf3");
+    let b = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+        .unwrap();
+    assert_eq!(san(&b, "e1g1"), "O-O");
+    assert_eq!(san(&b, "e1c1"), "O-O-O");
+    assert_eq!(san(&b, "d5e6"), "dxe6");
+    assert_eq!(san(&b, "e5f7"), "Nxf7");
+    assert_eq!(san(&b, "c3b1"), "Nb1");
+    assert_eq!(san(&b, "d2c1"), "Bc1");
+    assert_eq!(san(&b, "e2a6"), "Bxa6");
+    // disambiguation by rank and by square
+    let b = Board::from_fen("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1").unwrap();
+    assert_eq!(san(&b, "a1a3"), "R1a3");
+    let b = Board::from_fen("4k3/8/8/8/8/Q1Q5/8/Q3K3 w - - 0 1").unwrap();
+    assert_eq!(san(&b, "a3b2"), "Qa3b2");
+    // promotion with check and mate
+    let b = Board::from_fen("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(san(&b, "e7e8n"), "e8=N+");
+    let line = vec![b.parse_uci_move("e7e8q", false).unwrap()];
+    assert_eq!(b.line_to_san(&line).unwrap(), vec!["e8=Q"]);
+    let b = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    assert_eq!(san(&b, "a1a8"), "Ra8#");
+}
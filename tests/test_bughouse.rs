@@ -0,0 +1,96 @@
+extern crate rusty_board;
+use std::time::Duration;
+
+use rusty_board::board::*;
+use rusty_board::bughouse::*;
+use rusty_board::piece::*;
+use rusty_board::square::*;
+
+fn play(game: &mut BughouseGame, board: BoardId, san: &str, millis: u64) {
+    let m = game.get_board(board).parse_san(san).unwrap();
+    game.push(board, m, Duration::from_millis(millis)).unwrap();
+}
+
+#[test]
+fn test_bughouse_transfer() {
+    let mut game = BughouseGame::new();
+    play(&mut game, BoardId::A, "e4", 1000);
+    play(&mut game, BoardId::A, "d5", 2000);
+    play(&mut game, BoardId::A, "exd5", 3000);
+    // the captured pawn goes to the partner of White on board A
+    assert_eq!(game.get_board(BoardId::B).get_pocket(Color::Black, PieceKind::Pawn), 1);
+    assert_eq!(game.get_board(BoardId::A).get_pocket(Color::White, PieceKind::Pawn), 0);
+    play(&mut game, BoardId::B, "d4", 3500);
+    play(&mut game, BoardId::B, "P@e5", 4000);
+    assert_eq!(game.get_board(BoardId::B).get_pocket(Color::Black, PieceKind::Pawn), 0);
+    assert_eq!(game.get_moves().len(), 5);
+    let m = game.get_board(BoardId::A).parse_san("Qxd5").unwrap();
+    assert!(game.push(BoardId::A, m, Duration::from_millis(100)).is_err());
+    // promoted pieces are passed on as pawns
+    let mut game = BughouseGame::new();
+    play(&mut game, BoardId::A, "e4", 0);
+    play(&mut game, BoardId::A, "d5", 0);
+    play(&mut game, BoardId::A, "exd5", 0);
+    play(&mut game, BoardId::A, "c6", 0);
+    play(&mut game, BoardId::A, "dxc6", 0);
+    play(&mut game, BoardId::A, "Nf6", 0);
+    play(&mut game, BoardId::A, "cxb7", 0);
+    play(&mut game, BoardId::A, "Nbd7", 0);
+    play(&mut game, BoardId::A, "bxa8=Q", 0);
+    play(&mut game, BoardId::A, "Nb6", 0);
+    play(&mut game, BoardId::A, "Qd5", 0);
+    play(&mut game, BoardId::A, "Nbxd5", 0);
+    assert_eq!(game.get_board(BoardId::B).get_pocket(Color::White, PieceKind::Queen), 0);
+    assert_eq!(game.get_board(BoardId::B).get_pocket(Color::White, PieceKind::Pawn), 1);
+}
+
+#[test]
+fn test_bughouse_outcome() {
+    let mut game = BughouseGame::new();
+    play(&mut game, BoardId::A, "e4", 500);
+    play(&mut game, BoardId::B, "f3", 800);
+    play(&mut game, BoardId::B, "e5", 1200);
+    play(&mut game, BoardId::B, "g4", 1500);
+    assert!(game.outcome().is_none());
+    play(&mut game, BoardId::B, "Qh4#", 2000);
+    let outcome = game.outcome().unwrap();
+    assert_eq!(outcome.board, BoardId::B);
+    assert_eq!(outcome.outcome, Outcome::Checkmate(Color::Black));
+    // Black on board B plays with White on board A
+    assert_eq!(outcome.winner(), Some(Color::White));
+    assert_eq!(game.get_tag("Result"), Some("1-0"));
+    let m = game.get_board(BoardId::A).parse_san("e5").unwrap();
+    assert!(game.push(BoardId::A, m, Duration::from_millis(2500)).is_err());
+}
+
+#[test]
+fn test_bpgn() {
+    let mut game = BughouseGame::new();
+    game.set_tag("WhiteA", "Alice");
+    play(&mut game, BoardId::A, "e4", 1250);
+    play(&mut game, BoardId::B, "d4", 1300);
+    play(&mut game, BoardId::A, "d5", 2000);
+    play(&mut game, BoardId::A, "exd5", 2750);
+    play(&mut game, BoardId::B, "P@e5", 4001);
+    let bpgn = game.to_bpgn();
+    assert!(bpgn.contains("[WhiteA \"Alice\"]"));
+    assert!(bpgn.contains("1A. e4 {1.250} 1B. d4 {1.300} 1a. d5 {2.000} 2A. exd5 {2.750} \
+                           1b. P@e5 {4.001} *"));
+    let game2 = BughouseGame::from_bpgn(&bpgn).unwrap();
+    assert_eq!(game2.get_moves(), game.get_moves());
+    assert_eq!(game2.get_tags(), game.get_tags());
+    assert_eq!(game2.get_board(BoardId::B).to_fen(), game.get_board(BoardId::B).to_fen());
+    // move numbers glued to the moves and moves without times
+    let game3 = BughouseGame::from_bpgn("1A.e4 {0.5} 1B.Nf3 1a.e5 {1.5} 1-0").unwrap();
+    let times: Vec<u64> = game3.get_moves().iter().map(|m| m.time.as_millis() as u64).collect();
+    assert_eq!(times, [500, 500, 1500]);
+    // rest-of-line comments and variations are skipped, as in PGN
+    let game4 = BughouseGame::from_bpgn("1A. e4 {0.5} ; 1A. d4 {9}\n\
+                                         1B. Nf3 (1B. d4 {7.0} 1b. d5) {1.0} 1a. e5 {1.5} *")
+        .unwrap();
+    let times: Vec<u64> = game4.get_moves().iter().map(|m| m.time.as_millis() as u64).collect();
+    assert_eq!(times, [500, 1000, 1500]);
+    assert!(BughouseGame::from_bpgn("1A. e4 (1A. d4").is_err());
+    assert!(BughouseGame::from_bpgn("1A. e5").is_err());
+    assert!(BughouseGame::from_bpgn("e4").is_err());
+}
@@ -0,0 +1,67 @@
+extern crate rusty_board;
+use rusty_board::error::*;
+use rusty_board::board::*;
+use rusty_board::chess960::*;
+use rusty_board::piece::PieceKind::*;
+
+#[test]
+fn test_position_number() {
+    for n in 0..960 {
+        assert_eq!(Board::new_chess960(n).chess960_position_number(), Some(n));
+        assert_eq!(position_number(&back_rank(n)), Some(n));
+    }
+    assert_eq!(Board::new().chess960_position_number(), Some(518));
+    assert_eq!(back_rank(0), [Bishop, Bishop, Queen, Knight, Knight, Rook, King, Rook]);
+    // after a move the setup isn't recognized any more
+    let b = Board::new();
+    let b = b.do_move(b.parse_uci_move("g1f3", false).unwrap()).unwrap();
+    assert_eq!(b.chess960_position_number(), None);
+    // nor if the back ranks differ
+    let b = Board::from_fen("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNRNKR w - - 0 1").unwrap();
+    assert_eq!(b.chess960_position_number(), None);
+}
+
+#[test]
+fn test_validate_back_rank() {
+    assert!(validate_back_rank(&[Rook, Knight, Bishop, Queen, King, Bishop, Knight, Rook]).is_ok());
+    assert_eq!(validate_back_rank(&[Rook, Knight, Bishop, Bishop, King, Queen, Knight, Rook]),
+               Ok(()));
+    assert_eq!(validate_back_rank(&[Rook, Bishop, Knight, Bishop, King, Queen, Knight, Rook]),
+               Err(BoardError::InvalidPosition("Bishops must stand on squares of opposite \
+                                                colours")));
+    assert_eq!(validate_back_rank(&[King, Rook, Bishop, Queen, Rook, Bishop, Knight, Knight]),
+               Err(BoardError::InvalidPosition("King must stand between the rooks")));
+    assert_eq!(validate_back_rank(&[Rook, Knight, Bishop, Queen, King, Bishop, Knight, Queen]),
+               Err(BoardError::InvalidPosition("Wrong pieces on the back rank")));
+    assert!(validate_back_rank(&[Rook, Knight, Bishop]).is_err());
+    assert_eq!(position_number(&[King, Rook, Bishop, Queen, Rook, Bishop, Knight, Knight]), None);
+}
+
+#[test]
+fn test_random() {
+    let mut a = Random::new(42);
+    let mut b = Random::new(42);
+    let numbers: Vec<u32> = (0..100).map(|_| a.position_number()).collect();
+    assert_eq!(numbers, (0..100).map(|_| b.position_number()).collect::<Vec<u32>>());
+    assert!(numbers.iter().all(|&n| n < 960));
+    assert!(numbers.iter().any(|&n| n != numbers[0]));
+    let board = Board::new_chess960_random(&mut Random::new(7));
+    let n = Random::new(7).position_number();
+    assert_eq!(board.chess960_position_number(), Some(n));
+}
+
+#[test]
+fn test_capablanca_position_number() {
+    for n in 0..CAPABLANCA_POSITIONS {
+        assert_eq!(capablanca_position_number(&capablanca_back_rank(n)), Some(n));
+    }
+    let b = Board::new_capablanca_random(1234);
+    assert_eq!(b.get_variant().name(), "caparandom");
+    assert_eq!(b.chess960_position_number(), None);
+    let fen = b.to_fen();
+    assert_eq!(Board::from_fen_variant(&fen, b.get_variant()).unwrap().to_fen(), fen);
+    // queen and archbishop on the same colour
+    assert_eq!(capablanca_position_number(&[Rook, Queen, Knight, Archbishop, King, Bishop, Bishop,
+                                            Chancellor, Knight, Rook]),
+               None);
+}
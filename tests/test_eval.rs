@@ -0,0 +1,68 @@
+extern crate rusty_board;
+use rusty_board::board::*;
+use rusty_board::error::*;
+use rusty_board::eval::*;
+use rusty_board::moves::*;
+use rusty_board::piece::*;
+use rusty_board::square::*;
+use rusty_board::variant::*;
+
+#[test]
+fn test_eval_symmetric() {
+    let params = EvalParams::default();
+    let b = Board::new();
+    let e = evaluate_breakdown(&b, &params);
+    assert_eq!(e.total, 0);
+    assert_eq!(e.material, Score::new(0, 0));
+    assert_eq!(e.phase, e.max_phase);
+    assert_eq!(e.max_phase, 24);
+    for n in 0..960 {
+        assert_eq!(evaluate(&Board::new_chess960(n), &params), 0);
+    }
+}
+
+#[test]
+fn test_eval_side_to_move() {
+    let params = EvalParams::default();
+    let mut b = Board::new();
+    let white_pawn = Piece::new(PieceKind::Pawn, Color::White);
+    let e2 = Square::from_str("e2").unwrap();
+    let e4 = Square::from_str("e4").unwrap();
+    b.do_move_inplace(Move::new(white_pawn, e2, e4, None, None, false, false)).unwrap();
+    let e = evaluate_breakdown(&b, &params);
+    assert!(e.psqt.mg > 0);
+    assert!(e.mobility.mg > 0);
+    let text = e.to_string();
+    assert!(text.contains("mobility") && text.contains("king safety"));
+    assert!(text.ends_with(&format!("side to move: {}", e.total)));
+}
+
+#[test]
+fn test_params_parse() {
+    let params = EvalParams::default();
+    assert_eq!(EvalParams::parse(&params.to_string()), Ok(params.clone()));
+    let tuned = EvalParams::parse("# tuned\nmaterial_mg = 100 300 300 500 900 0\n\n\
+                                   doubled_pawn = -5 -5 # comment")
+        .unwrap();
+    assert_eq!(tuned.material_mg, [100, 300, 300, 500, 900, 0]);
+    assert_eq!(tuned.doubled_pawn, [-5, -5]);
+    assert_eq!(tuned.material_eg, params.material_eg);
+    assert_eq!(EvalParams::parse("\nmaterial_mg = 1 2"),
+               Err(ParamsError::WrongCount(2, "material_mg".to_string(), 6, 2)));
+    assert_eq!(EvalParams::parse("no_such_weight = 1"),
+               Err(ParamsError::UnknownWeight(1, "no_such_weight".to_string())));
+    assert_eq!(EvalParams::parse("psqt_mg_pawn"), Err(ParamsError::Syntax(1)));
+    let e = EvalParams::parse("pawn_shield = 1 x").unwrap_err();
+    assert_eq!(e, ParamsError::Value(1, "x".to_string()));
+    assert_eq!(e.to_string(), "line 1: invalid value `x`");
+    assert!(matches!(EvalParams::load("/no/such/file"), Err(ParamsError::Io(_))));
+}
+
+#[test]
+fn test_eval_without_king() {
+    // the pawns of the horde don't shield a king that isn't there
+    let b = Board::from_fen_variant("4k3/8/8/8/8/8/3PPP2/8 w - - 0 1", &HORDE).unwrap();
+    let e = evaluate_breakdown(&b, &EvalParams::default());
+    assert_eq!(e.king_safety.mg, 0);
+    assert_eq!(e.king_safety.eg, 0);
+}
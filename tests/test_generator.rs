@@ -1,12 +1,208 @@
 extern crate rusty_board;
 use rusty_board::board::*;
-use rusty_board::square::*;
+use rusty_board::error::*;
 use rusty_board::generator::*;
+use rusty_board::moves::*;
+use rusty_board::piece::*;
+use rusty_board::square::*;
+use rusty_board::variant::*;
 
 #[test]
 fn test_board() {
     let b = Board::new();
-    let moves = generate_pseudo_legal_moves(b);
+    let moves = generate_pseudo_legal_moves(&b);
     println!("{:?}", moves);
     assert_eq!(moves.len(), 20);
 }
+
+#[test]
+fn test_perft() {
+    assert_eq!(perft(Board::new(), 1), 20);
+    assert_eq!(perft(Board::new(), 2), 400);
+    assert_eq!(perft(Board::new(), 3), 8902);
+    // "Kiwipete": castling, en passant and promotions
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    assert_eq!(perft(Board::from_fen(kiwipete).unwrap(), 1), 48);
+    assert_eq!(perft(Board::from_fen(kiwipete).unwrap(), 2), 2039);
+    let endgame = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+    assert_eq!(perft(Board::from_fen(endgame).unwrap(), 3), 2812);
+    let promotions = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+    assert_eq!(perft(Board::from_fen(promotions).unwrap(), 2), 264);
+    let position5 = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+    assert_eq!(perft(Board::from_fen(position5).unwrap(), 2), 1486);
+}
+
+#[test]
+fn test_perft_chess960() {
+    let positions = [("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", 21, 528),
+                     ("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", 21, 807),
+                     ("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9", 20, 479)];
+    for &(fen, d1, d2) in &positions {
+        let b = Board::from_fen(fen).unwrap();
+        assert_eq!(perft(b.clone(), 1), d1);
+        assert_eq!(perft(b, 2), d2);
+    }
+    assert_eq!(perft(Board::new_chess960(0), 2), 400);
+}
+
+#[test]
+fn test_move_validation() {
+    // moves of other positions (and variants) must be recognized exactly
+    let mut pool: Vec<Move> = Vec::new();
+    for &v in variants() {
+        let mut b = Board::new_variant(v);
+        for ply in 0..40 {
+            let pseudo = generate_pseudo_legal_moves(&b);
+            let legal = generate_legal_moves(b.clone());
+            pool.extend(pseudo.iter().cloned());
+            for &m in pool.iter().rev().take(2000) {
+                // all pseudo-legal antichess moves are legal, compulsory captures included
+                let expected = if v.name() == "antichess" { &legal } else { &pseudo };
+                assert_eq!(b.is_pseudo_legal(m), expected.contains(&m), "{} {:?}", b.to_fen(), m);
+                assert_eq!(b.is_legal(m), legal.contains(&m), "{} {:?}", b.to_fen(), m);
+            }
+            if legal.is_empty() {
+                break;
+            }
+            b.do_move_inplace(legal[(ply * 7) % legal.len()]).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_checked_moves() {
+    let mut b = Board::from_fen("4k3/8/8/8/8/8/8/R3K2r w Q - 0 1").unwrap();
+    let fen = b.to_fen();
+    let sq = |s: &str| Square::from_str(s).unwrap();
+    let rook = Piece::new(PieceKind::Rook, Color::White);
+    let king = Piece::new(PieceKind::King, Color::White);
+    // a rook moving diagonally is only caught by the checked mode
+    let diagonal = Move::new(rook, sq("a1"), sq("b2"), None, None, false, false);
+    assert!(!b.is_pseudo_legal(diagonal));
+    assert!(b.do_move(diagonal).is_ok());
+    assert_eq!(b.do_move_inplace_checked(diagonal), Err(MoveError::NotPseudoLegal(diagonal)));
+    // staying in check
+    let kd1 = Move::new(king, sq("e1"), sq("d1"), None, None, false, false);
+    assert!(b.is_pseudo_legal(kd1) && !b.is_legal(kd1));
+    assert_eq!(b.do_move_inplace_checked(kd1), Err(MoveError::IllegalMove(kd1)));
+    let castling = Move::new(king, sq("e1"), sq("c1"), None, None, false, true);
+    assert!(!b.is_pseudo_legal(castling));
+    assert_eq!(b.to_fen(), fen);
+    // mismatching pieces are reported first
+    let pawn = Piece::new(PieceKind::Pawn, Color::White);
+    let ghost = Move::new(pawn, sq("c2"), sq("c3"), None, None, false, false);
+    assert_eq!(b.do_move_inplace_checked(ghost), Err(MoveError::EmptySquare(ghost, sq("c2"))));
+    let kd2 = Move::new(king, sq("e1"), sq("d2"), None, None, false, false);
+    assert!(b.is_legal(kd2));
+    b.do_move_inplace_checked(kd2).unwrap();
+    assert_eq!(b.to_fen(), "4k3/8/8/8/8/8/3K4/R6r b - - 1 1");
+}
+
+/// Runs the generator to the end, the board must be left as it was.
+fn collect_moves(mut gen: MoveGen, board: &mut Board) -> Vec<Move> {
+    let fen = board.to_fen();
+    let mut moves = Vec::new();
+    while let Some(m) = gen.next_move(board) {
+        moves.push(m);
+    }
+    assert_eq!(board.to_fen(), fen);
+    moves
+}
+
+#[test]
+fn test_move_gen() {
+    let positions = [
+        ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+         &STANDARD as &dyn Variant),
+        ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", &STANDARD),
+        ("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R[Pn] w KQkq - 2 3", &CRAZYHOUSE),
+        ("rnbqkbnr/pppp1ppp/8/4p3/3P4/8/PPP1PPPP/RNBQKBNR w - - 0 2", &ANTICHESS),
+    ];
+    let sorted = |mut moves: Vec<Move>| {
+        moves.sort_by_key(|m| format!("{:?}", m));
+        moves
+    };
+    for &(fen, variant) in &positions {
+        let mut b = Board::from_fen_variant(fen, variant).unwrap();
+        // compulsory captures are applied, as by `is_pseudo_legal()`
+        let pseudo: Vec<Move> = generate_pseudo_legal_moves(&b)
+            .into_iter()
+            .filter(|&m| b.is_pseudo_legal(m))
+            .collect();
+        let quiets: Vec<Move> = pseudo.iter()
+            .cloned()
+            .filter(|m| m.get_captured_piece().is_none())
+            .collect();
+        let hash = pseudo[pseudo.len() / 2];
+        let killers = [quiets.first().cloned(), quiets.last().cloned()];
+        let mut gen = MoveGen::new(MoveFilter::All).with_hash_move(Some(hash))
+            .with_killers(killers);
+        let mut moves = Vec::new();
+        let mut last_stage = Stage::HashMove;
+        while let Some(m) = gen.next_move(&mut b) {
+            let stage = gen.get_stage();
+            assert!(stage >= last_stage);
+            match stage {
+                Stage::HashMove => assert_eq!(m, hash),
+                Stage::GoodCaptures => assert!(b.see(m) >= 0),
+                Stage::Killers => assert!(killers.contains(&Some(m))),
+                Stage::Quiets => assert!(m.get_captured_piece().is_none()),
+                Stage::BadCaptures => assert!(b.see(m) < 0),
+                Stage::Done => unreachable!(),
+            }
+            last_stage = stage;
+            moves.push(m);
+        }
+        assert_eq!(moves[0], hash);
+        assert_eq!(sorted(moves), sorted(pseudo.clone()), "{}", fen);
+        // filters
+        let mut filters = vec![MoveFilter::Captures,
+                               MoveFilter::Quiets,
+                               MoveFilter::Promotions,
+                               MoveFilter::Checks];
+        for m in &pseudo {
+            filters.push(MoveFilter::From(m.get_square_from()));
+            filters.push(MoveFilter::To(m.get_square_to()));
+        }
+        for filter in filters {
+            let expected: Vec<Move> = pseudo.iter()
+                .cloned()
+                .filter(|&m| filter.accepts(&mut b, m))
+                .collect();
+            let gen = MoveGen::new(filter).with_hash_move(Some(hash)).with_killers(killers);
+            let moves = collect_moves(gen, &mut b);
+            assert_eq!(sorted(moves), sorted(expected), "{} {:?}", fen, filter);
+        }
+    }
+    // good captures come by most valuable victim, moves of other positions are left out
+    let mut b = Board::from_fen("4k3/8/3q1r2/4P3/8/8/8/R3K3 w - - 0 1").unwrap();
+    let other = Board::new().parse_uci_move("e2e4", false).unwrap();
+    let gen = MoveGen::new(MoveFilter::Captures).with_hash_move(Some(other))
+        .with_killers([Some(other), None]);
+    let captures: Vec<String> = collect_moves(gen, &mut b)
+        .iter()
+        .map(|&m| b.move_to_uci(m, false))
+        .collect();
+    assert_eq!(captures, vec!["e5d6", "e5f6"]);
+    let checks: Vec<String> = collect_moves(MoveGen::new(MoveFilter::Checks), &mut b)
+        .iter()
+        .map(|&m| b.move_to_uci(m, false))
+        .collect();
+    assert_eq!(checks, vec!["a1a8"]);
+    // only the captures are generated by kind, castling and drops are quiet moves
+    let b = Board::from_fen_variant("r3k3/8/8/8/8/8/7r/R3K3[N] w Qq - 0 1", &CRAZYHOUSE).unwrap();
+    let mut captures = MoveList::new();
+    b.get_variant().pseudo_legal_moves(&b, MoveKinds::Captures, &mut captures);
+    assert_eq!(captures.len(), 1);
+    let mut quiets = MoveList::new();
+    b.get_variant().pseudo_legal_moves(&b, MoveKinds::Quiets, &mut quiets);
+    assert_eq!(captures.len() + quiets.len(), generate_pseudo_legal_moves(&b).len());
+    assert!(quiets.iter().any(|m| m.is_castling()) && quiets.iter().any(|m| m.is_drop()));
+    // promotions capturing nothing, without the other quiet moves
+    let b = Board::from_fen_variant("r3k3/1P6/8/8/8/8/8/4K3[N] w - - 0 1", &CRAZYHOUSE).unwrap();
+    let mut promotions = MoveList::new();
+    b.get_variant().pseudo_legal_moves(&b, MoveKinds::Promotions, &mut promotions);
+    assert_eq!(promotions.len(), 4);
+    assert!(promotions.iter()
+        .all(|m| m.get_promoted_to().is_some() && m.get_captured_piece().is_none()));
+}
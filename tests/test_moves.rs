@@ -0,0 +1,86 @@
+extern crate rusty_board;
+use rusty_board::board::*;
+use rusty_board::generator::*;
+use rusty_board::moves::*;
+use rusty_board::piece::*;
+use rusty_board::square::*;
+use rusty_board::variant::*;
+
+#[test]
+fn test_packed_move_round_trip() {
+    let positions = [
+        ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+         &STANDARD as &dyn Variant),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", &STANDARD),
+        ("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1", &STANDARD),
+        ("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", &CHESS960),
+        ("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R[Pn] w KQkq - 2 3", &CRAZYHOUSE),
+        ("8/P7/8/8/8/8/8/k6K w - - 0 1", &ANTICHESS),
+        ("rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1", &GARDNER),
+    ];
+    for &(fen, variant) in &positions {
+        let b = Board::from_fen_variant(fen, variant).unwrap();
+        let moves = generate_pseudo_legal_moves(&b);
+        assert!(!moves.is_empty());
+        for m in moves {
+            let packed = PackedMove::new(m).unwrap();
+            assert_eq!(PackedMove::from_u16(packed.to_u16()), packed);
+            assert_eq!(b.expand(packed), Some(m), "{} {:?}", fen, m);
+        }
+    }
+}
+
+#[test]
+fn test_packed_move() {
+    let b = Board::new();
+    let e4 = b.parse_uci_move("e2e4", false).unwrap();
+    let packed = PackedMove::new(e4).unwrap();
+    assert_eq!(packed.to_u16(), 12 | 28 << 6);
+    assert_eq!(packed.get_square_to(), Square::from_str("e4").unwrap());
+    assert_eq!(packed.get_promoted_to(), None);
+    // a move of another position doesn't expand
+    let after = b.do_move(e4).unwrap();
+    assert_eq!(after.expand(packed), None);
+    assert_eq!(b.expand(PackedMove::from_u16(0)), None);
+    // squares beyond the 8x8 board can't be packed
+    let capa = Board::new_variant(&CAPABLANCA);
+    let j3 = capa.parse_uci_move("j2j3", false).unwrap();
+    assert_eq!(PackedMove::new(j3), None);
+    let promotion = Board::from_fen("7k/P7/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    let m = promotion.parse_uci_move("a7a8n", false).unwrap();
+    assert_eq!(PackedMove::new(m).unwrap().get_promoted_to(), Some(PieceKind::Knight));
+}
+
+#[test]
+fn test_move_list() {
+    let b = Board::new();
+    let moves = generate_legal_moves(b.clone());
+    let mut list: MoveList = moves.iter().cloned().collect();
+    assert_eq!(list.len(), 20);
+    assert_eq!(&list[..], &moves[..]);
+    // scores stay attached to their moves, equal scores keep the order
+    list.score_moves(|m| if m.get_moving_piece().get_kind() == PieceKind::Knight { 1 } else { 0 });
+    list.sort_by_score();
+    assert!(list[..4].iter().all(|m| m.get_moving_piece().get_kind() == PieceKind::Knight));
+    assert_eq!(list[4], moves.iter().find(|m| m.get_moving_piece().get_kind() == PieceKind::Pawn)
+        .cloned()
+        .unwrap());
+    assert_eq!(list.get_score(0), 1);
+    list.retain(|m| m.get_moving_piece().get_kind() == PieceKind::Pawn);
+    assert_eq!(list.len(), 16);
+    assert!((0..list.len()).all(|i| list.get_score(i) == 0));
+    let last = list[15];
+    assert_eq!(list.into_iter().last(), Some(last));
+    // drops make lists longer than the inline storage
+    let b = Board::from_fen_variant("k7/8/8/8/8/8/8/K7[QRBNPqrbnp] w - - 0 1", &CRAZYHOUSE)
+        .unwrap();
+    let mut moves = generate_legal_moves(b.clone());
+    assert_eq!(moves.len(), 4 * 62 + 48 + 3);
+    assert!(moves.len() > MAX_MOVES);
+    moves.set_score(moves.len() - 1, 5);
+    moves.sort_by_score();
+    assert_eq!(moves.get_score(0), 5);
+    moves.retain(|m| !m.is_drop());
+    assert_eq!(moves.len(), 3);
+    assert_eq!(perft(b, 1), 299);
+}
@@ -0,0 +1,93 @@
+extern crate rusty_board;
+use rusty_board::error::*;
+use rusty_board::board::*;
+use rusty_board::chess960::Random;
+use rusty_board::generator::*;
+use rusty_board::pgn::*;
+use rusty_board::square::*;
+
+#[test]
+fn test_pgn_read() {
+    let text = r#"[Event "Casual game"]
+[Site "?"]
+[White "Anderssen"]
+[Black "Kieseritzky"]
+[Result "1-0"]
+
+1. e4 e5 2. f4 exf4 3. Bc4 Qh4+ {an early queen sortie} 4. Kf1 b5?! 5. Bxb5 Nf6
+6. Nf3 Qh6 7. d3 (7. Nc3 $1) 7... Nh5 8. Nh4 Qg5 9. Nf5 c6 10. g4 Nf6 11. Rg1 cxb5
+12. h4 Qg6 13. h5 Qg5 14. Qf3 Ng8 15. Bxf4 Qf6 16. Nc3 Bc5 17. Nd5 Qxb2 18. Bd6
+Bxg1 19. e5 Qxa1+ 20. Ke2 Na6 21. Nxg7+ Kd8 22. Qf6+ Nxf6 23. Be7# 1-0
+"#;
+    let game = Game::from_pgn(text).unwrap();
+    assert_eq!(game.get_tag("White"), Some("Anderssen"));
+    assert_eq!(game.get_moves().len(), 45);
+    let b = game.board();
+    assert_eq!(b.outcome(), Some(Outcome::Checkmate(Color::White)));
+    assert_eq!(Game::from_pgn("1. e4 e5 2. Ke3 *").err().map(|e| e.to_string()),
+               Some("Illegal move Ke3".to_string()));
+    assert!(Game::from_pgn("1. e4 {unterminated").is_err());
+    // castling with zeros, a black first move and glued move numbers
+    let game = Game::from_pgn("[FEN \"r3k3/8/8/8/8/8/8/4K2R b Kq - 0 20\"]\n\n20...0-0-0 21.O-O *")
+        .unwrap();
+    assert_eq!(game.board().to_fen(), "2kr4/8/8/8/8/8/8/5RK1 b - - 2 21");
+    assert!(game.to_pgn().ends_with("\n20... O-O-O 21. O-O *\n"));
+}
+
+#[test]
+fn test_pgn_write() {
+    let mut game = Game::new(Board::new());
+    game.set_tag("White", "A \"quoted\" name");
+    for s in &["e2e4", "e7e5", "g1f3"] {
+        let m = game.board().parse_uci_move(s, false).unwrap();
+        game.push(m).unwrap();
+    }
+    let e4 = Board::new().parse_uci_move("e2e4", false).unwrap();
+    assert_eq!(game.push(e4), Err(MoveError::IllegalMove(e4)));
+    let pgn = game.to_pgn();
+    assert!(pgn.starts_with("[Event \"?\"]\n"));
+    assert!(pgn.contains("[White \"A \\\"quoted\\\" name\"]\n"));
+    assert!(!pgn.contains("FEN"));
+    assert!(pgn.ends_with("\n\n1. e4 e5 2. Nf3 *\n"));
+    let read = Game::from_pgn(&pgn).unwrap();
+    assert_eq!(read.get_tag("White"), Some("A \"quoted\" name"));
+    assert_eq!(read.get_moves(), game.get_moves());
+}
+
+/// Plays random games from Double Chess960 setups, castling whenever possible.
+#[test]
+fn test_double_chess960_round_trip() {
+    let mut rng = Random::new(2024);
+    let mut castlings = 0;
+    for _ in 0..20 {
+        let (w, b) = (rng.position_number(), rng.position_number());
+        let start = Board::new_double_chess960(w, b);
+        assert_eq!(start.get_initial_king_file(Color::Black),
+                   Board::new_chess960(b).get_initial_king_file(Color::White));
+        let fen = start.to_fen();
+        assert_eq!(Board::from_fen(&fen).unwrap().to_fen(), fen);
+        let mut game = Game::new(start);
+        assert_eq!(game.get_tag("FEN"), Some(fen.as_str()));
+        for _ in 0..60 {
+            let moves = generate_legal_moves(game.board());
+            if moves.is_empty() {
+                break;
+            }
+            let m = match moves.iter().find(|m| m.is_castling()) {
+                Some(&m) => m,
+                None => moves[(rng.next_u64() % moves.len() as u64) as usize],
+            };
+            castlings += m.is_castling() as u32;
+            game.push(m).unwrap();
+            let board = game.board();
+            assert_eq!(Board::from_fen(&board.to_fen()).unwrap().to_fen(), board.to_fen());
+        }
+        let read = Game::from_pgn(&game.to_pgn()).unwrap();
+        assert_eq!(read.get_moves(), game.get_moves());
+        assert_eq!(read.board().to_fen(), game.board().to_fen());
+        if w != b {
+            assert_eq!(read.get_tag("Variant"), Some("Chess960"));
+        }
+    }
+    assert!(castlings > 10);
+}
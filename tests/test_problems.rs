@@ -0,0 +1,72 @@
+extern crate rusty_board;
+use rusty_board::board::*;
+use rusty_board::problems::*;
+use rusty_board::square::*;
+
+fn keys(fen: &str, stipulation: Stipulation) -> Vec<String> {
+    let b = Board::from_fen(fen).unwrap();
+    solve(&b, stipulation).keys.iter().map(|&m| b.move_to_san(m)).collect()
+}
+
+#[test]
+fn test_direct_mate() {
+    assert_eq!(keys("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", Stipulation::Mate(1)), vec!["Ra8#"]);
+    // Morphy: 1. Ra6! bxa6 2. b7#
+    let b = Board::from_fen("kbK5/pp6/1P6/8/8/8/8/R7 w - - 0 1").unwrap();
+    let solution = solve(&b, Stipulation::Mate(2));
+    assert_eq!(solution.keys, vec![b.parse_uci_move("a1a6", false).unwrap()]);
+    assert!(solution.is_solved() && !solution.is_cooked());
+    assert!(solution.duals.is_empty());
+    // refuted
+    assert!(!solve(&b, Stipulation::Mate(1)).is_solved());
+    assert!(!solve(&Board::new(), Stipulation::Mate(2)).is_solved());
+}
+
+#[test]
+fn test_cooks_and_duals() {
+    // short mates count, so almost everything works
+    let b = Board::from_fen("7k/8/6K1/8/8/8/8/R7 w - - 0 1").unwrap();
+    let solution = solve(&b, Stipulation::Mate(2));
+    assert!(solution.is_cooked());
+    let intended = b.parse_uci_move("a1a8", false).unwrap();
+    assert_eq!(solution.cooks(intended).len(), solution.keys.len() - 1);
+    // after 1. Kg6 Kg8 both 2. Ra8# and 2. Rb8# mate
+    let b = Board::from_fen("7k/8/5K2/8/8/8/8/RR6 w - - 0 1").unwrap();
+    let solution = solve(&b, Stipulation::Mate(2));
+    assert_eq!(solution.duals.len(), 1);
+    let dual = &solution.duals[0];
+    let after = b.do_move(dual.line[0]).unwrap();
+    assert_eq!(after.line_to_san(&dual.line[1..]).unwrap(), vec!["Kg8"]);
+    let after = after.do_move(dual.line[1]).unwrap();
+    let moves: Vec<String> = dual.moves.iter().map(|&m| after.move_to_san(m)).collect();
+    assert_eq!(moves, vec!["Ra8#", "Rb8#"]);
+}
+
+#[test]
+fn test_selfmate_and_helpmate() {
+    // 1. Qb7+ Bxb7#
+    assert_eq!(keys("k7/5Q2/KP6/6r1/8/8/4R1bp/8 w - - 0 1", Stipulation::Selfmate(1)),
+               vec!["Qb7+"]);
+    assert!(keys("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", Stipulation::Selfmate(1)).is_empty());
+    // 1. Kg8 Ra8#
+    assert_eq!(keys("7k/8/6K1/8/8/8/8/R7 b - - 0 1", Stipulation::Helpmate(1)), vec!["Kg8"]);
+    let b = Board::from_fen("k7/8/1K6/8/8/8/8/7R b - - 0 1").unwrap();
+    let solution = solve(&b, Stipulation::Helpmate(2));
+    assert_eq!(solution.keys.len(), 1);
+    assert_eq!(solution.duals.len(), 1);
+    assert_eq!(format!("{}", Stipulation::Helpmate(2)), "h#2");
+}
+
+#[test]
+fn test_outcome() {
+    let b = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+    assert_eq!(b.outcome(), Some(Outcome::Checkmate(Color::White)));
+    assert_eq!(b.outcome().unwrap().winner(), Some(Color::White));
+    let b = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(b.outcome(), Some(Outcome::Stalemate));
+    let b = Board::from_fen("7k/8/6K1/8/8/8/8/6N1 b - - 0 1").unwrap();
+    assert_eq!(b.outcome(), Some(Outcome::InsufficientMaterial));
+    let b = Board::from_fen("7k/8/6K1/8/8/8/8/R7 b - - 100 80").unwrap();
+    assert_eq!(b.outcome(), Some(Outcome::FiftyMoveRule));
+    assert_eq!(Board::new().outcome(), None);
+}
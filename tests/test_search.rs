@@ -0,0 +1,89 @@
+extern crate rusty_board;
+use rusty_board::board::*;
+use rusty_board::search::*;
+
+fn best_move(fen: &str, depth: u32) -> (String, SearchScore) {
+    let b = Board::from_fen(fen).unwrap();
+    let limits = SearchLimits { depth: Some(depth), ..SearchLimits::default() };
+    let result = Search::new().run(&b, &limits, |_| {});
+    (b.move_to_uci(result.best_move.unwrap(), false), result.score)
+}
+
+#[test]
+fn test_search_mate() {
+    assert_eq!(best_move("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 2),
+               ("a1a8".to_string(), SearchScore::Mate(1)));
+    // 1. Nf6+ gxf6 2. Bxf7#
+    let fen = "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1";
+    assert_eq!(best_move(fen, 4), ("d5f6".to_string(), SearchScore::Mate(2)));
+    // the side to move is getting mated
+    let (_, score) = best_move("6k1/8/8/8/8/r7/1r6/7K w - - 0 1", 3);
+    assert_eq!(score, SearchScore::Mate(-1));
+}
+
+#[test]
+fn test_search_no_moves() {
+    let b = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    let result = Search::new().run(&b, &SearchLimits::default(), |_| {});
+    assert_eq!(result.best_move, None);
+    assert_eq!(result.score, SearchScore::Centipawns(0));
+    let b = Board::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    let result = Search::new().run(&b, &SearchLimits::default(), |_| {});
+    assert_eq!(result.best_move, None);
+    assert_eq!(result.score, SearchScore::Mate(0));
+}
+
+#[test]
+fn test_search_wins_material() {
+    // the queen is hanging
+    let (m, _) = best_move("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", 3);
+    assert_eq!(m, "d2d5");
+    let (m, _) = best_move("4k3/8/8/3q4/8/8/8/4KR2 w - - 0 1", 3);
+    assert!(m != "f1f5");
+}
+
+#[test]
+fn test_search_threads() {
+    let fen = "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1";
+    let b = Board::from_fen(fen).unwrap();
+    let mut search = Search::new();
+    search.set_threads(4);
+    search.set_hash_size(4);
+    let limits = SearchLimits { depth: Some(4), ..SearchLimits::default() };
+    let mut nodes = 0;
+    let result = search.run(&b, &limits, |info| nodes = info.nodes);
+    assert_eq!(b.move_to_uci(result.best_move.unwrap(), false), "d5f6");
+    assert_eq!(result.score, SearchScore::Mate(2));
+    // helper threads' nodes are counted too
+    assert!(result.nodes >= nodes);
+    // a second search profits from the shared hash table
+    let again = search.run(&b, &limits, |_| {});
+    assert_eq!(again.best_move, result.best_move);
+    assert!(again.nodes < result.nodes);
+    search.clear_hash();
+}
+
+#[test]
+fn test_search_multi_pv() {
+    // only Ra8 mates, the other lines are ordered by score
+    let b = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let mut search = Search::new();
+    search.set_multi_pv(3);
+    let limits = SearchLimits { depth: Some(3), ..SearchLimits::default() };
+    let mut reported = Vec::new();
+    let result = search.run(&b, &limits, |info| reported.push(info.multipv));
+    assert_eq!(reported, vec![1, 2, 3, 1, 2, 3]);
+    assert_eq!(result.lines.len(), 3);
+    assert_eq!(result.lines[0].pv, result.pv);
+    assert_eq!(result.lines[0].score, SearchScore::Mate(1));
+    assert_eq!(b.line_to_san(&result.lines[0].pv).unwrap(), vec!["Ra8#"]);
+    assert!(result.lines.windows(2).all(|w| w[0].pv[0] != w[1].pv[0]));
+    match result.lines[1].score {
+        SearchScore::Centipawns(cp) => assert!(cp > 0),
+        s => panic!("unexpected score {:?}", s),
+    }
+    // more lines than legal moves
+    let b = Board::from_fen("7k/8/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    search.set_multi_pv(10);
+    assert_eq!(search.run(&b, &limits, |_| {}).lines.len(), 3);
+}
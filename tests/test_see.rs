@@ -0,0 +1,69 @@
+extern crate rusty_board;
+use rusty_board::board::*;
+use rusty_board::moves::*;
+use rusty_board::square::*;
+
+/// Moves a piece without any checks, used to set up test positions.
+fn place(b: &mut Board, from: &str, to: &str) {
+    let from = Square::from_str(from).unwrap();
+    let to = Square::from_str(to).unwrap();
+    let m = Move::new(b.get_piece(from).unwrap(), from, to, b.get_piece(to), None, false, false);
+    b.do_move_inplace(m).unwrap();
+}
+
+fn make_move(b: &Board, from: &str, to: &str) -> Move {
+    let from = Square::from_str(from).unwrap();
+    let to = Square::from_str(to).unwrap();
+    Move::new(b.get_piece(from).unwrap(), from, to, b.get_piece(to), None, false, false)
+}
+
+#[test]
+fn test_see_simple() {
+    let mut b = Board::new();
+    place(&mut b, "d7", "d5");
+    place(&mut b, "d1", "h5");
+    // Qxd5 Qxd5 loses the queen for a pawn
+    assert_eq!(b.see(make_move(&b, "h5", "d5")), 100 - 1000);
+    assert!(!b.see_ge(make_move(&b, "h5", "d5"), 0));
+    place(&mut b, "e2", "e4");
+    // exd5 wins a pawn, since Qxd5 is met by Qxd5
+    assert_eq!(b.see(make_move(&b, "e4", "d5")), 100);
+    assert!(b.see_ge(make_move(&b, "e4", "d5"), 100));
+    assert!(!b.see_ge(make_move(&b, "e4", "d5"), 101));
+    // and now Qxd5 Qxd5 exd5 is fine as well
+    assert_eq!(b.see(make_move(&b, "h5", "d5")), 100);
+    // a quiet move to an attacked square hangs the piece
+    assert_eq!(b.see(make_move(&b, "h5", "h6")), -1000);
+    assert_eq!(b.see(make_move(&b, "h5", "g4")), -1000);
+    assert_eq!(b.see(make_move(&b, "h5", "h4")), 0);
+}
+
+#[test]
+fn test_see_xray() {
+    let mut b = Board::new();
+    place(&mut b, "e2", "a3");
+    place(&mut b, "a1", "e3");
+    place(&mut b, "d1", "e2");
+    place(&mut b, "e7", "e5");
+    place(&mut b, "d7", "d6");
+    // Rxe5 dxe5 Qxe5: the queen behind the rook recaptures
+    assert_eq!(b.see(make_move(&b, "e3", "e5")), 200 - 500);
+    place(&mut b, "b8", "c6");
+    // now the knight recaptures the queen, so White stops after dxe5
+    assert_eq!(b.see(make_move(&b, "e3", "e5")), 100 - 500);
+}
+
+#[test]
+fn test_see_king() {
+    let mut b = Board::new();
+    place(&mut b, "e1", "e4");
+    place(&mut b, "d7", "d5");
+    place(&mut b, "d8", "h4");
+    place(&mut b, "g1", "f4");
+    place(&mut b, "b8", "b6");
+    // Nxd5 Nxd5 Kxd5
+    assert_eq!(b.see(make_move(&b, "f4", "d5")), 100);
+    place(&mut b, "c7", "c6");
+    // Nxd5 cxd5 and the king can't recapture, as the knight still defends d5
+    assert_eq!(b.see(make_move(&b, "f4", "d5")), 100 - 325);
+}
@@ -5,6 +5,17 @@ use rusty_board::square::*;
 fn test_square() {
     let e5: Square = Square::from_str("e5").unwrap();
     assert_eq!(format!("{}", e5), "e5");
+    assert_eq!(Square::from_str("j10").unwrap().to_string(), "j10");
+    // board arrays are indexed by file and rank on the largest board, not 0 to 63
+    let a2 = Square::from_str("a2").unwrap();
+    assert_eq!(a2.as_index(), 10);
+    assert_eq!(Square::from_index(10), a2);
+    assert_eq!(a2.to_int(), Some(8));
+    assert_eq!(Square::from_int(8), a2);
+    assert_eq!(Square::from_str("i1").unwrap().to_int(), None);
+    for bad in &["e+2", "e-2", "e 2", "e02", "e11", "k1", "e"] {
+        assert_eq!(Square::from_str(bad), None, "{}", bad);
+    }
 }
 
 #[test]
@@ -47,3 +58,24 @@ fn test_adjacent() {
     assert_eq!(h8.get_knight_moves().len(), 2);
     assert_eq!(b1.get_knight_moves().len(), 3);
 }
+
+#[test]
+fn test_geometry() {
+    let g = Geometry::CAPABLANCA;
+    assert_eq!((g.get_files(), g.get_ranks()), (10, 8));
+    assert_eq!(g.get_last_file(), File::J);
+    assert_eq!(g.back_rank(Color::Black), Rank::Eighth);
+    assert_eq!(g.squares().count(), 80);
+    assert_eq!(g.squares().count(), g.square_count());
+    assert!(Geometry::new(11, 8).is_none());
+    let j1 = Square::from_str("j1").unwrap();
+    assert!(g.contains(j1));
+    assert!(!Geometry::STANDARD.contains(j1));
+    assert_eq!(format!("{}", j1), "j1");
+    let h1 = Square::from_str("h1").unwrap();
+    assert_eq!(h1.get_by_dir_in(Direction::Right, Geometry::STANDARD), None);
+    assert_eq!(h1.get_by_offset(2, 0, g), Some(j1));
+    assert_eq!(j1.get_knight_moves_in(g).len(), 2);
+    // the standard numbering is kept for the 8x8 board
+    assert_eq!(Square::from_int(63), Square::from_str("h8").unwrap());
+}
@@ -0,0 +1,76 @@
+extern crate rusty_board;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rusty_board::board::*;
+use rusty_board::search::*;
+use rusty_board::timeman::*;
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+fn limits(remaining: u64, increment: Increment, moves_to_go: Option<u32>) -> (u64, u64) {
+    let tc = TimeControl { remaining: ms(remaining), increment, moves_to_go };
+    let tm = TimeManager::new(&tc, ms(0), Arc::new(ManualClock::new()));
+    (tm.soft_limit().as_millis() as u64, tm.hard_limit().as_millis() as u64)
+}
+
+#[test]
+fn test_time_allocation() {
+    // sudden death
+    assert_eq!(limits(60000, Increment::None, None), (2000, 8000));
+    // Fischer
+    assert_eq!(limits(60000, Increment::Fischer(ms(1000)), None), (2750, 11000));
+    // classical, the last move before the control
+    assert_eq!(limits(10000, Increment::None, Some(1)), (7500, 9000));
+    assert_eq!(limits(10000, Increment::None, Some(40)), (250, 1000));
+    // a simple delay isn't charged, a Bronstein delay is only given back afterwards
+    assert_eq!(limits(1000, Increment::Delay(ms(2000)), None), (2033, 2500));
+    assert_eq!(limits(1000, Increment::Bronstein(ms(2000)), None), (250, 500));
+    // the move overhead is kept in reserve
+    let tc = TimeControl { remaining: ms(100), ..TimeControl::default() };
+    let tm = TimeManager::new(&tc, ms(200), Arc::new(ManualClock::new()));
+    assert!(tm.should_stop() && tm.should_abort());
+    let tm = TimeManager::fixed(ms(1000), ms(100), Arc::new(ManualClock::new()));
+    assert_eq!((tm.soft_limit(), tm.hard_limit()), (ms(900), ms(900)));
+}
+
+#[test]
+fn test_time_extensions() {
+    let clock = ManualClock::new();
+    let tc = TimeControl { remaining: ms(60000), ..TimeControl::default() };
+    let mut tm = TimeManager::new(&tc, ms(0), Arc::new(clock.clone()));
+    tm.on_iteration(false, 20);
+    clock.advance(ms(2500));
+    assert!(tm.should_stop());
+    // unstable best move
+    tm.on_iteration(true, 20);
+    assert_eq!(tm.soft_limit(), ms(3000));
+    assert!(!tm.should_stop());
+    // score drop
+    tm.on_iteration(false, -130);
+    assert_eq!(tm.soft_limit(), ms(4000));
+    tm.on_iteration(true, -130);
+    assert_eq!(tm.soft_limit(), ms(5000));
+    clock.advance(ms(5000));
+    assert!(tm.should_stop() && !tm.should_abort());
+    clock.advance(ms(1000));
+    assert!(tm.should_abort());
+    tm.restart();
+    assert_eq!(tm.elapsed(), ms(0));
+}
+
+#[test]
+fn test_search_time_limit() {
+    let mut search = Search::new();
+    search.set_clock(Arc::new(ManualClock::new()));
+    let limits = SearchLimits {
+        time_control: Some(TimeControl { remaining: ms(10), ..TimeControl::default() }),
+        move_overhead: ms(50),
+        ..SearchLimits::default()
+    };
+    let result = search.run(&Board::new(), &limits, |_| {});
+    assert!(result.best_move.is_some());
+    assert_eq!(result.depth, 1);
+}
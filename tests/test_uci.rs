@@ -0,0 +1,81 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Pipes a scripted session into the engine and returns its output.
+fn session(input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn bestmove(output: &str) -> &str {
+    let line = output.lines().find(|l| l.starts_with("bestmove")).unwrap();
+    line.split_whitespace().nth(1).unwrap()
+}
+
+#[test]
+fn test_uci_handshake() {
+    let output = session("uci\nisready\nquit\n");
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[0].starts_with("id name"));
+    assert!(lines.contains(&"option name UCI_Chess960 type check default false"));
+    assert!(lines.contains(&"option name UCI_Variant type combo default chess var chess \
+                             var chess960 var crazyhouse var bughouse var atomic var 3check \
+                             var kingofthehill var antichess var horde var racingkings \
+                             var capablanca var gothic var caparandom var gardner \
+                             var losalamos"));
+    assert_eq!(&lines[lines.len() - 2..], &["uciok", "readyok"]);
+}
+
+#[test]
+fn test_uci_go() {
+    let output = session("uci\nucinewgame\nposition fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\n\
+                          go depth 3\n");
+    assert_eq!(bestmove(&output), "a1a8");
+    assert!(output.contains("score mate 1"));
+    let output = session("setoption name Threads value 3
+setoption name Hash value 4
+                          position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1
+go depth 4
+");
+    assert_eq!(bestmove(&output), "a1a8");
+    assert!(!output.contains("info string"));
+    let output = session("setoption name MultiPV value 2\n\
+                          position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 2\n");
+    assert!(output.contains(" multipv 1 score mate 1 "));
+    assert!(output.contains(" multipv 2 score cp "));
+    let output = session("position startpos moves e2e4 e7e5 g1f3\ngo nodes 2000\n");
+    let info = output.lines().find(|l| l.starts_with("info depth 1 ")).unwrap();
+    for field in &[" score cp ", " nodes ", " nps ", " pv "] {
+        assert!(info.contains(field));
+    }
+    let output = session("position startpos\ngo wtime 1000 btime 1000 winc 10 binc 10 \
+                          movestogo 20\n");
+    assert_eq!(output.lines().filter(|l| l.starts_with("bestmove")).count(), 1);
+    let output = session("position startpos moves e2e5\n");
+    assert!(output.contains("info string illegal move e2e5"));
+}
+
+#[test]
+fn test_uci_stop_and_ponder() {
+    let output = session("position startpos\ngo infinite\nstop\nquit\n");
+    assert!(output.contains("bestmove"));
+    let output = session("position startpos\ngo ponder movetime 50\nponderhit\n");
+    assert!(output.contains("bestmove"));
+}
+
+#[test]
+fn test_uci_chess960() {
+    let output = session("setoption name UCI_Chess960 value true\n\
+                          position fen 1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1 moves g1h1 g8h8\n\
+                          go depth 1\n");
+    assert!(!output.contains("illegal"));
+    let output = session("setoption name UCI_Chess960 value true\n\
+                          position fen 6k1/8/8/8/8/8/8/R3K2R w HA - 0 1\ngo depth 1\n");
+    assert!(!output.contains("e1g1") && !output.contains("e1c1"));
+}
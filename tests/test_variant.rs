@@ -0,0 +1,281 @@
+extern crate rusty_board;
+use rusty_board::board::*;
+use rusty_board::error::*;
+use rusty_board::generator::*;
+use rusty_board::pgn::*;
+use rusty_board::piece::*;
+use rusty_board::search::*;
+use rusty_board::square::*;
+use rusty_board::variant::*;
+
+#[test]
+fn test_variant_registry() {
+    assert_eq!(variant_by_name("chess").unwrap().name(), "chess");
+    assert_eq!(variant_by_name("Chess960").unwrap().name(), "chess960");
+    assert_eq!(variant_by_name("fischerandom").unwrap().name(), "chess960");
+    assert!(variant_by_name("shogi").is_none());
+    // perft 3 from the starting positions
+    let perft3 = [("chess", 8902), ("chess960", 8902), ("crazyhouse", 8902), ("bughouse", 8902),
+                  ("atomic", 8902), ("3check", 8902), ("kingofthehill", 8902),
+                  ("antichess", 8067), ("horde", 1274), ("racingkings", 11264),
+                  ("capablanca", 25228), ("gothic", 25283), ("caparandom", 25283),
+                  ("gardner", 506), ("losalamos", 1212)];
+    assert_eq!(variants().len(), perft3.len());
+    for &(name, nodes) in &perft3 {
+        let v = variant_by_name(name).unwrap();
+        let b = Board::new_variant(v);
+        assert_eq!(b.get_variant().name(), v.name());
+        let fen = b.to_fen();
+        let b2 = Board::from_fen_variant(&fen, v).unwrap();
+        assert_eq!(b2.to_fen(), fen);
+        assert_eq!(b2.get_hash(), b.get_hash());
+        assert_eq!(perft(b, 3), nodes);
+    }
+}
+
+#[test]
+fn test_variant_dispatch() {
+    assert_eq!(Board::new().get_variant().name(), "chess");
+    let b = Board::new_chess960(0);
+    assert!(b.get_variant().is_chess960());
+    assert_eq!(Board::from_fen(&b.to_fen()).unwrap().get_variant().name(), "chess");
+    // the PGN Variant tag selects the rules
+    let game = Game::new(b.clone());
+    assert_eq!(game.get_tag("Variant"), Some("Chess960"));
+    let read = Game::from_pgn(&game.to_pgn()).unwrap();
+    assert!(read.get_start().get_variant().is_chess960());
+    assert_eq!(read.get_start().to_fen(), b.to_fen());
+    assert!(Game::from_pgn("[Variant \"Shogi\"]\n\n*").is_err());
+}
+
+#[test]
+fn test_crazyhouse() {
+    let zh = |fen: &str| Board::from_fen_variant(fen, &CRAZYHOUSE).unwrap();
+    // drops on every empty square, pawns not on the first and the last rank
+    assert_eq!(perft(zh("4k3/8/8/8/8/8/8/4K3[N] w - - 0 1"), 1), 5 + 62);
+    assert_eq!(perft(zh("4k3/8/8/8/8/8/8/4K3[P] w - - 0 1"), 1), 5 + 48);
+    // interposing by a drop
+    assert_eq!(perft(zh("4k3/8/8/8/8/8/8/r3K3[N] w - - 0 1"), 1), 3 + 3);
+    // captured pieces change sides, a promoted piece is captured as a pawn
+    let mut b = zh("r2k4/1P6/1n6/8/8/8/8/4K3[] w - - 0 1");
+    let start = b.to_fen();
+    let m = b.parse_san("bxa8=Q+").unwrap();
+    b.do_move_inplace(m).unwrap();
+    assert_eq!(b.to_fen(), "Q~2k4/8/1n6/8/8/8/8/4K3[R] b - - 0 1");
+    let m = b.parse_san("Nxa8").unwrap();
+    b.do_move_inplace(m).unwrap();
+    assert_eq!(b.to_fen(), "n2k4/8/8/8/8/8/8/4K3[Rp] w - - 0 2");
+    assert_eq!(b.get_pocket(Color::Black, PieceKind::Pawn), 1);
+    assert_eq!(zh(&b.to_fen()).get_hash(), b.get_hash());
+    let drop = b.parse_uci_move("R@d1", false).unwrap();
+    assert!(drop.is_drop());
+    assert_eq!(b.move_to_san(drop), "R@d1+");
+    b.do_move_inplace(drop).unwrap();
+    assert_eq!(b.get_pocket(Color::White, PieceKind::Rook), 0);
+    assert_eq!(b.parse_san("@d7"), b.parse_uci_move("P@d7", false));
+    for _ in 0..3 {
+        b.undo_move_inplace().unwrap();
+    }
+    assert_eq!(b.to_fen(), start);
+    // the ninth rank notation for pockets
+    assert_eq!(zh("4k3/8/8/8/8/8/8/4K3/Qn w - - 0 1").to_fen(),
+               "4k3/8/8/8/8/8/8/4K3[Qn] w - - 0 1");
+    // pockets hold at most 32 pieces of a kind, all counts hash differently
+    let empty = zh("4k3/8/8/8/8/8/8/4K3[] w - - 0 1");
+    let full = zh(&format!("4k3/8/8/8/8/8/8/4K3[{}] w - - 0 1", "P".repeat(32)));
+    assert_eq!(full.get_pocket(Color::White, PieceKind::Pawn), 32);
+    assert_ne!(full.get_hash(), empty.get_hash());
+    let overfull = format!("4k3/8/8/8/8/8/8/4K3[{}] w - - 0 1", "p".repeat(300));
+    assert_eq!(Board::from_fen_variant(&overfull, &CRAZYHOUSE).err(),
+               Some(BoardError::Fen("Too many pieces in a pocket", "p".repeat(300))));
+    let long_run = "8/8/8/8/8/8/8/99999999999[] w - - 0 1";
+    assert_eq!(Board::from_fen_variant(long_run, &CRAZYHOUSE).err(),
+               Some(BoardError::Fen("Wrong number of files",
+                                    "8/8/8/8/8/8/8/99999999999".to_string())));
+    let game = Game::new(Board::new_variant(&CRAZYHOUSE));
+    assert_eq!(game.get_tag("Variant"), Some("Crazyhouse"));
+    assert_eq!(game.get_tag("FEN"), None);
+}
+
+#[test]
+fn test_atomic() {
+    let atomic = |fen: &str| Board::from_fen_variant(fen, &ATOMIC).unwrap();
+    // the capture blows up the rook next to the target square, but not the pawns
+    let mut b = atomic("4k3/8/8/2Pnr3/4P3/8/8/4K3 w - - 0 1");
+    let fen = b.to_fen();
+    let m = b.parse_san("exd5").unwrap();
+    b.do_move_inplace(m).unwrap();
+    assert_eq!(b.to_fen(), "4k3/8/8/2P5/8/8/8/4K3 b - - 0 1");
+    assert_eq!(atomic(&b.to_fen()).get_hash(), b.get_hash());
+    b.undo_move_inplace().unwrap();
+    assert_eq!(b.to_fen(), fen);
+    // kings don't capture, and nothing may blow up the own king
+    assert_eq!(perft(atomic("4k3/8/8/8/8/8/4p3/4K3 w - - 0 1"), 1), 2);
+    assert_eq!(atomic("4k3/8/8/8/8/8/4n3/3QK3 w - - 0 1").parse_uci_move("d1e2", false), None);
+    // touching kings don't give check
+    assert!(!atomic("8/8/8/8/8/8/R2kK3/8 b - - 0 1").is_in_check());
+    // so the king may castle next to the enemy king, but not through an attack
+    let castlings = |fen: &str| {
+        generate_legal_moves(atomic(fen)).iter().filter(|m| m.is_castling()).count()
+    };
+    assert_eq!(castlings("8/8/8/8/8/8/6k1/4K2R w K - 0 1"), 1);
+    assert_eq!(castlings("k4r2/8/8/8/8/8/8/4K2R w K - 0 1"), 0);
+    // exploding the enemy king wins, even out of check
+    let b = atomic("4k3/3q4/8/8/8/8/8/3RK1r1 w - - 0 1");
+    let after = b.do_move(b.parse_uci_move("d1d7", false).unwrap()).unwrap();
+    assert_eq!(after.outcome(), Some(Outcome::VariantWin(Color::White)));
+    let limits = SearchLimits { depth: Some(2), ..SearchLimits::default() };
+    let result = Search::new().run(&b, &limits, |_| {});
+    assert_eq!(b.move_to_uci(result.best_move.unwrap(), false), "d1d7");
+    assert_eq!(result.score, SearchScore::Mate(1));
+}
+
+#[test]
+fn test_three_check() {
+    let three_check = |fen: &str| Board::from_fen_variant(fen, &THREE_CHECK).unwrap();
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let b = three_check(&format!("{} +2+2", kiwipete));
+    assert_eq!(perft(b.clone(), 3), 97848);
+    // the Lichess field with remaining checks
+    let b2 = three_check(&kiwipete.replace("- 0 1", "- 1+1 0 1"));
+    assert_eq!(b2.to_fen(), b.to_fen());
+    assert_eq!(b2.get_hash(), b.get_hash());
+    assert!(three_check(kiwipete).get_hash() != b.get_hash());
+    assert!(Board::from_fen_variant(&format!("{} +4+0", kiwipete), &THREE_CHECK).is_err());
+    // the third check wins
+    let mut b = three_check("4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +2+0");
+    assert_eq!(b.get_checks_remaining(Color::White), 1);
+    let m = b.parse_san("Ra8+").unwrap();
+    b.do_move_inplace(m).unwrap();
+    assert_eq!(b.get_checks_remaining(Color::White), 0);
+    assert_eq!(b.outcome(), Some(Outcome::VariantWin(Color::White)));
+    assert_eq!(perft(b.clone(), 1), 0);
+    b.undo_move_inplace().unwrap();
+    assert_eq!(b.to_fen(), "4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +2+0");
+    let limits = SearchLimits { depth: Some(2), ..SearchLimits::default() };
+    assert_eq!(Search::new().run(&b, &limits, |_| {}).score, SearchScore::Mate(1));
+}
+
+#[test]
+fn test_king_of_the_hill() {
+    let b = Board::from_fen_variant("8/8/8/8/8/4K3/8/k7 w - - 0 1", &KING_OF_THE_HILL).unwrap();
+    // bare kings still play on
+    assert_eq!(b.outcome(), None);
+    let after = b.do_move(b.parse_san("Kd4").unwrap()).unwrap();
+    assert_eq!(after.outcome(), Some(Outcome::VariantWin(Color::White)));
+    assert_eq!(perft(after, 1), 0);
+    let limits = SearchLimits { depth: Some(2), ..SearchLimits::default() };
+    let result = Search::new().run(&b, &limits, |_| {});
+    assert_eq!(result.score, SearchScore::Mate(1));
+    let after = b.do_move(result.best_move.unwrap()).unwrap();
+    assert_eq!(after.outcome(), Some(Outcome::VariantWin(Color::White)));
+}
+
+#[test]
+fn test_antichess() {
+    let anti = |fen: &str| Board::from_fen_variant(fen, &ANTICHESS).unwrap();
+    // captures are compulsory, even for the king
+    let b = anti("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1");
+    assert_eq!(generate_legal_moves(b.clone()).to_vec(), vec![b.parse_san("Kxd2").unwrap()]);
+    // promotion to king
+    let b = anti("8/1P6/8/8/8/8/8/7k w - - 0 1");
+    assert_eq!(perft(b.clone(), 1), 5);
+    let m = b.parse_uci_move("b7b8k", false).unwrap();
+    assert_eq!(b.move_to_san(m), "b8=K");
+    // losing all pieces or having no moves wins
+    let b = anti("8/8/8/8/8/8/8/Rr6 w - - 0 1");
+    let after = b.do_move(b.parse_san("Rxb1").unwrap()).unwrap();
+    assert_eq!(after.outcome(), Some(Outcome::VariantWin(Color::Black)));
+    let limits = SearchLimits { depth: Some(2), ..SearchLimits::default() };
+    assert_eq!(Search::new().run(&b, &limits, |_| {}).score, SearchScore::Mate(-1));
+    let b = anti("8/8/8/8/8/p7/P7/8 w - - 0 1");
+    assert_eq!(b.outcome(), Some(Outcome::VariantWin(Color::White)));
+    assert!(Board::from_fen("8/8/8/8/8/p7/P7/8 w - - 0 1").is_err());
+    // any number of kings, but no pawns on the rank they promote on
+    assert_eq!(perft(anti("8/8/8/8/8/8/8/K6K w - - 0 1"), 1), 6);
+    assert_eq!(Board::from_fen_variant("8/8/8/8/8/8/8/p6K b - - 0 1", &ANTICHESS).err(),
+               Some(BoardError::Fen("Pawn on the first or the last rank", "p6K".to_string())));
+    assert!(Board::from_fen_variant("P7/8/8/8/8/8/8/7k w - - 0 1", &ANTICHESS).is_err());
+}
+
+#[test]
+fn test_horde() {
+    let horde = |fen: &str| Board::from_fen_variant(fen, &HORDE).unwrap();
+    let b = Board::new_variant(&HORDE);
+    assert_eq!(perft(b.clone(), 4), 23310);
+    // double steps from the first rank, without en passant
+    let b = horde("4k3/8/8/8/8/8/8/4P3 w - - 0 1");
+    assert_eq!(perft(b.clone(), 1), 2);
+    let b = b.do_move(b.parse_uci_move("e1e3", false).unwrap()).unwrap();
+    assert_eq!(b.get_en_passant_square(), None);
+    // White loses with the last piece, but a single pawn is enough to play on
+    let b = horde("r3k3/8/8/8/8/8/8/P7 b - - 0 1");
+    assert_eq!(b.outcome(), None);
+    let after = b.do_move(b.parse_san("Rxa1").unwrap()).unwrap();
+    assert_eq!(after.outcome(), Some(Outcome::VariantWin(Color::Black)));
+    // only White pawns may stand on their first rank
+    for fen in &["P3k3/8/8/8/8/8/8/8 w - - 0 1", "4k3/8/8/8/8/8/8/p3P3 b - - 0 1",
+                 "p3k3/8/8/8/8/8/8/P7 b - - 0 1"] {
+        assert!(Board::from_fen_variant(fen, &HORDE).is_err(), "{}", fen);
+    }
+}
+
+#[test]
+fn test_racing_kings() {
+    let racing = |fen: &str| Board::from_fen_variant(fen, &RACING_KINGS).unwrap();
+    // giving check is illegal
+    let b = racing("8/8/8/8/8/k7/8/R6K w - - 0 1");
+    assert_eq!(b.parse_uci_move("a1a2", false), None);
+    assert!(b.parse_uci_move("a1b1", false).is_some());
+    // Black may equalize after White reaches the eighth rank first
+    let b = racing("8/6K1/k7/8/8/8/8/8 w - - 0 1");
+    let after = b.do_move(b.parse_san("Kg8").unwrap()).unwrap();
+    assert_eq!(after.outcome(), Some(Outcome::VariantWin(Color::White)));
+    let b = racing("8/k5K1/8/8/8/8/8/8 w - - 0 1");
+    let after = b.do_move(b.parse_san("Kg8").unwrap()).unwrap();
+    assert_eq!(after.outcome(), None);
+    let after = after.do_move(after.parse_san("Kb8").unwrap()).unwrap();
+    assert_eq!(after.outcome(), Some(Outcome::VariantDraw));
+}
+
+#[test]
+fn test_capablanca() {
+    let capa = |fen: &str| Board::from_fen_variant(fen, &CAPABLANCA).unwrap();
+    let b = Board::new_variant(&CAPABLANCA);
+    assert_eq!(b.to_fen(), "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1");
+    assert_eq!(b.get_piece(Square::from_str("c1").unwrap()),
+               Some(Piece::new(PieceKind::Archbishop, Color::White)));
+    // the king castles to the i-file or the c-file
+    let b = capa("r4k3r/10/10/10/10/10/10/R4K3R w KQkq - 0 1");
+    let after = b.do_move(b.parse_san("O-O").unwrap()).unwrap();
+    assert_eq!(after.to_fen(), "r4k3r/10/10/10/10/10/10/R6RK1 b kq - 1 1");
+    let after = b.do_move(b.parse_san("O-O-O").unwrap()).unwrap();
+    assert_eq!(after.to_fen(), "r4k3r/10/10/10/10/10/10/2KR5R b kq - 1 1");
+    // pawns may also promote to the compound pieces
+    let b = capa("5k4/P9/10/10/10/10/10/5K4 w - - 0 1");
+    let after = b.do_move(b.parse_san("a8=C+").unwrap()).unwrap();
+    assert_eq!(after.to_fen(), "C4k4/10/10/10/10/10/10/5K4 b - - 0 1");
+    assert!(b.parse_san("a8=A").is_some());
+    assert_eq!(perft(b, 1), 11);
+}
+
+#[test]
+fn test_minichess() {
+    let b = Board::new_variant(&GARDNER);
+    assert_eq!(b.to_fen(), "rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1");
+    assert_eq!(perft(b, 4), 4775);
+    let b = Board::new_variant(&LOS_ALAMOS);
+    assert_eq!(b.to_fen(), "rnqknr/pppppp/6/6/PPPPPP/RNQKNR w - - 0 1");
+    assert_eq!(perft(b, 4), 14332);
+    // pawns step one square only
+    let b = Board::from_fen_variant("3k1/5/5/P4/4K w - - 0 1", &GARDNER).unwrap();
+    assert!(b.parse_uci_move("a2a4", false).is_none());
+    assert!(b.parse_uci_move("a2a3", false).is_some());
+    // no castling, even with the rights given
+    let b = Board::from_fen_variant("r2k1r/6/6/6/6/R2K1R w KQ - 0 1", &LOS_ALAMOS).unwrap();
+    assert!(b.parse_san("O-O").is_none());
+    // Los Alamos pawns don't promote to bishops
+    let b = Board::from_fen_variant("5k/P5/6/6/6/K5 w - - 0 1", &LOS_ALAMOS).unwrap();
+    assert!(b.parse_san("a6=B").is_none());
+    assert_eq!(perft(b, 1), 3 + 3);
+}
@@ -0,0 +1,65 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Pipes a scripted session into the engine and returns its output.
+fn session(input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_xboard"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn moves(output: &str) -> Vec<&str> {
+    output.lines().filter(|l| l.starts_with("move ")).map(|l| &l[5..]).collect()
+}
+
+#[test]
+fn test_xboard_features() {
+    let output = session("xboard\nprotover 2\nping 7\nquit\n");
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[0].starts_with("feature "));
+    assert!(lines[0].contains("setboard=1") && lines[0].contains("usermove=1"));
+    assert!(lines[0].contains("fischerandom"));
+    assert_eq!(&lines[1..], &["feature done=1", "pong 7"]);
+}
+
+#[test]
+fn test_xboard_game() {
+    let output = session("xboard\nprotover 2\nnew\nsd 2\nusermove e2e4\n");
+    assert_eq!(moves(&output).len(), 1);
+    // in force mode the engine only keeps the board
+    let output = session("new\nforce\nusermove e2e4\nusermove e7e5\nundo\nremove\nusermove e2e5\n");
+    assert!(moves(&output).is_empty());
+    assert!(output.contains("Illegal move: e2e5"));
+    let output = session("new\nforce\nusermove e2e4\nundo\nundo\n");
+    assert!(output.contains("Error"));
+    let output = session("new\nsetboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\npost\nsd 3\ngo\n");
+    assert_eq!(moves(&output), vec!["a1a8"]);
+    assert!(output.contains(" 100001 "));
+    assert!(output.contains("1-0 {White mates}"));
+    let output = session("new\nlevel 40 0:30 0\nst 1\nforce\nusermove e2e4\ngo\n");
+    assert_eq!(moves(&output).len(), 1);
+    // a flagged clock still leaves time for a move
+    let output = session("new\nlevel 40 0:30 0\ntime -120\nforce\nusermove e2e4\ngo\n");
+    assert_eq!(moves(&output).len(), 1);
+}
+
+#[test]
+fn test_xboard_fischerandom() {
+    let output = session("new\nvariant fischerandom\n\
+                          setboard 1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1\nforce\n\
+                          usermove O-O\nusermove g8h8\nusermove b1a1\nsd 1\ngo\n");
+    assert!(!output.contains("Illegal"));
+    assert_eq!(moves(&output).len(), 1);
+}
+
+#[test]
+fn test_xboard_go_while_aborting() {
+    // the second `go` arrives before the aborted search has returned
+    let output = session("new\nst 1\ngo\nnew\nst 1\ngo\n");
+    assert_eq!(moves(&output).len(), 1);
+}